@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::api::local_mods::move_file;
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
+
+/// How many replaced versions are kept for each mod.
+pub const MAX_ARCHIVED_VERSIONS: usize = 5;
+const ENTRY_FILE: &str = "entry.json";
+
+/// A mod file that an update replaced, kept with the manifest entry it had.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedVersion {
+    pub file_name: String,
+    pub entry: InstalledModEntry,
+    /// Milliseconds since the Unix epoch when the file was replaced.
+    pub archived_at: u64,
+    /// Folder holding the file and its entry.
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl ArchivedVersion {
+    pub fn file_path(&self) -> PathBuf {
+        self.path.join(&self.file_name)
+    }
+}
+
+/// Where replaced versions are kept, in the app's data folder.
+pub fn get_archive_dir(settings: &AppSettings) -> PathBuf {
+    settings.get_data_dir().join("archive")
+}
+
+/// Checks that `id` names exactly one folder, so ids read from a provider or
+/// an archive's manifest can't reach outside the archive.
+fn folder_name(id: &str) -> Result<&str, String> {
+    let mut components = Path::new(id).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) if !id.contains(['/', '\\', '\0']) => Ok(id),
+        _ => Err(format!("{:?} can't be used as a folder name", id)),
+    }
+}
+
+fn mod_archive_dir(settings: &AppSettings, api_provider: &ApiProvider, mod_id: &str) -> Result<PathBuf, String> {
+    Ok(get_archive_dir(settings).join(api_provider.id()).join(folder_name(mod_id)?))
+}
+
+/// Moves `file`, the replaced copy of `entry`, into the archive, and drops the
+/// oldest versions of the mod beyond `MAX_ARCHIVED_VERSIONS`.
+pub fn archive_file(settings: &AppSettings, file_name: &str, entry: &InstalledModEntry, file: &Path) -> Result<(), String> {
+    let dir = mod_archive_dir(settings, &entry.api_provider, &entry.mod_id)?.join(folder_name(&entry.file_id)?);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let archived = ArchivedVersion {
+        file_name: file_name.to_string(),
+        entry: entry.clone(),
+        archived_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64),
+        path: dir.clone(),
+    };
+    move_file(file, &archived.file_path()).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&archived).map_err(|e| e.to_string())?;
+    fs::write(dir.join(ENTRY_FILE), json).map_err(|e| e.to_string())?;
+
+    for old in archived_versions(settings, &entry.api_provider, &entry.mod_id).into_iter().skip(MAX_ARCHIVED_VERSIONS) {
+        let _ = fs::remove_dir_all(old.path);
+    }
+    Ok(())
+}
+
+/// The archived versions of a mod, most recently replaced first.
+pub fn archived_versions(settings: &AppSettings, api_provider: &ApiProvider, mod_id: &str) -> Vec<ArchivedVersion> {
+    let Ok(entries) = mod_archive_dir(settings, api_provider, mod_id).and_then(|dir| fs::read_dir(dir).map_err(|e| e.to_string())) else {
+        return vec![];
+    };
+
+    let mut versions: Vec<ArchivedVersion> = entries.flatten()
+        .filter_map(|dir| {
+            let json = fs::read_to_string(dir.path().join(ENTRY_FILE)).ok()?;
+            let mut archived: ArchivedVersion = serde_json::from_str(&json).ok()?;
+            archived.path = dir.path();
+            archived.file_path().is_file().then_some(archived)
+        })
+        .collect();
+    versions.sort_by_key(|v| std::cmp::Reverse(v.archived_at));
+    versions
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use md5::Md5;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgo {
+    Sha1,
+    Md5,
+    Sha256,
+}
+
+impl HashAlgo {
+    /// CurseForge's `FileHash.algo` codes.
+    pub fn from_curseforge(algo: i32) -> Option<Self> {
+        match algo {
+            1 => Some(HashAlgo::Sha1),
+            2 => Some(HashAlgo::Md5),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgo::Sha1 => "SHA-1",
+            HashAlgo::Md5 => "MD5",
+            HashAlgo::Sha256 => "SHA-256",
+        }
+    }
+
+    /// Lowercase hex digest of `data`.
+    pub fn digest(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgo::Sha1 => to_hex(&Sha1::digest(data)),
+            HashAlgo::Sha256 => to_hex(&Sha256::digest(data)),
+            HashAlgo::Md5 => to_hex(&Md5::digest(data)),
+        }
+    }
+}
+
+/// A checksum published by a provider for one file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileChecksum {
+    pub algo: HashAlgo,
+    pub value: String,
+}
+
+impl FileChecksum {
+    pub fn new(algo: HashAlgo, value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        (!value.is_empty()).then_some(Self { algo, value })
+    }
+}
+
+/// Checks a download against the size and every checksum the provider
+/// published. Files without either pass unchecked.
+pub fn verify(data: &[u8], expected_size: Option<u64>, checksums: &[FileChecksum]) -> Result<(), String> {
+    let mut verifier = Verifier::new(checksums);
+    verifier.update(data);
+    verifier.finish(expected_size)
+}
+
+enum Hasher {
+    Sha1(Sha1),
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashAlgo::Md5 => Hasher::Md5(Md5::new()),
+            HashAlgo::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        match self {
+            Hasher::Sha1(h) => h.update(chunk),
+            Hasher::Md5(h) => h.update(chunk),
+            Hasher::Sha256(h) => h.update(chunk),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Hasher::Sha1(h) => to_hex(&h.finalize()),
+            Hasher::Md5(h) => to_hex(&h.finalize()),
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+        }
+    }
+}
+
+/// Incremental form of [`verify`] for downloads that are streamed to disk
+/// rather than held in memory.
+pub struct Verifier {
+    received: u64,
+    hashers: Vec<(FileChecksum, Hasher)>,
+}
+
+impl Verifier {
+    pub fn new(checksums: &[FileChecksum]) -> Self {
+        Self {
+            received: 0,
+            hashers: checksums.iter().map(|c| (c.clone(), Hasher::new(c.algo))).collect(),
+        }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.received += chunk.len() as u64;
+        for (_, hasher) in &mut self.hashers {
+            hasher.update(chunk);
+        }
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received
+    }
+
+    pub fn finish(self, expected_size: Option<u64>) -> Result<(), String> {
+        if let Some(size) = expected_size.filter(|s| *s > 0) {
+            if self.received != size {
+                return Err(format!(
+                    "Download is incomplete: expected {} bytes, got {}",
+                    size,
+                    self.received
+                ));
+            }
+        }
+
+        for (checksum, hasher) in self.hashers {
+            let actual = hasher.finish();
+            if actual != checksum.value {
+                return Err(format!(
+                    "{} checksum mismatch: expected {}, got {}",
+                    checksum.algo.name(),
+                    checksum.value,
+                    actual
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -1,16 +1,24 @@
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, USER_AGENT};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, RANGE, USER_AGENT};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, OnceLock, RwLock};
-use reqwest::Client;
+use std::sync::{Arc, RwLock};
+use reqwest::{Client, Response};
+use crate::api::http::{RequestLayer, RetryPolicy, TokenBucket};
+use crate::api::provider::{BaseUrls, ModProvider, DETAILS_CONCURRENCY};
+use crate::api::settings::ApiProvider;
+use crate::api::ui_mod::{pick_version, SearchFilter, UiCategory, UiMod, UiModVersion, VersionPage};
 
 const CURSEFORGE_API: &str = "https://api.curseforge.com/v1";
 const HYTALE_GAME_ID: u32 = 70216;
-
-static CLIENT: OnceLock<RwLock<Arc<Client>>> = OnceLock::new();
-
-fn client_store() -> &'static RwLock<Arc<Client>> {
-    CLIENT.get_or_init(|| RwLock::new(Arc::new(build_client(None))))
-}
+const FILES_PAGE_SIZE: u32 = 50;
+const MODS_BATCH_SIZE: usize = 50;
+/// CurseForge rejects requests where `index + pageSize` exceeds 10 000.
+const FILES_INDEX_LIMIT: u32 = 10_000 - FILES_PAGE_SIZE;
+/// Requests allowed in a burst, and per second after it. CurseForge doesn't
+/// publish its limits, so these are kept conservative.
+const REQUESTS_BURST: u32 = 20;
+const REQUESTS_PER_SECOND: f64 = 10.0;
 
 fn build_client(api_key: Option<&str>) -> Client {
     let mut headers = HeaderMap::new();
@@ -18,7 +26,12 @@ fn build_client(api_key: Option<&str>) -> Client {
     headers.insert(USER_AGENT, HeaderValue::from_static("HytaleModManager/1.0"));
 
     if let Some(key) = api_key {
-        headers.insert("x-api-key", HeaderValue::from_str(key).unwrap());
+        match HeaderValue::from_str(key) {
+            Ok(val) => {
+                headers.insert("x-api-key", val);
+            }
+            Err(_) => eprintln!("Ignoring CurseForge API key: it contains characters a header can't hold."),
+        }
     }
 
     Client::builder()
@@ -27,20 +40,6 @@ fn build_client(api_key: Option<&str>) -> Client {
         .expect("Failed to build HTTP client")
 }
 
-fn client() -> Arc<Client> {
-    client_store().read().unwrap().clone()
-}
-
-
-pub fn set_global_api_key(key: &str) {
-    let new_client = Arc::new(build_client(Some(key)));
-
-    if let Ok(mut lock) = client_store().write() {
-        *lock = new_client;
-        println!("Global API key applied to all future requests on Curse Forge.");
-    }
-}
-
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiFile {
@@ -52,11 +51,16 @@ pub struct ApiFile {
     pub release_type: u8,
     pub download_url: Option<String>,
     pub game_versions: Vec<String>,
+    #[serde(default)]
+    pub hashes: Vec<FileHash>,
+    #[serde(default)]
+    pub dependencies: Vec<FileDependency>,
 }
 
 #[derive(Debug, Deserialize)]
 struct GetFilesResponse {
     pub data: Vec<ApiFile>,
+    pub pagination: Option<Pagination>,
 }
 
 
@@ -180,164 +184,485 @@ pub struct FileDependency {
     pub relation_type: i32,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetModsRequest<'a> {
+    mod_ids: &'a [u32],
+    filter_pc_only: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GameData {
     pub id: u32,
     pub name: String,
 }
 
-pub async fn search_mods(query: String, search_sort: u32, offset: u32) -> Result<(Vec<CurseForgeMod>, Option<Pagination>), String> {
-    let url = format!("{}/mods/search", CURSEFORGE_API);
-    let search_filter = if query.trim().is_empty() { "" } else { &query };
+pub struct CurseForgeProvider {
+    client: RwLock<Arc<Client>>,
+    requests: RequestLayer,
+    base_urls: RwLock<BaseUrls>,
+    game_version: RwLock<Option<String>>,
+    /// The category tree rarely changes, so it is fetched once per session.
+    categories: RwLock<Option<Vec<Category>>>,
+}
 
-    let params = [
-        ("gameId", HYTALE_GAME_ID.to_string()),
-        ("searchFilter", search_filter.to_string()),
-        ("category", "0".to_string()),
-        ("pageSize", "20".to_string()),
-        ("sortField", (search_sort + 1).to_string()),
-        ("sortOrder", "desc".to_string()),
-        ("index", offset.to_string()),
-    ];
+impl Default for CurseForgeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    let resp = client()
-        .get(&url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("Network Request Failed: {}", e))?;
+impl CurseForgeProvider {
+    pub fn new() -> Self {
+        Self {
+            client: RwLock::new(Arc::new(build_client(None))),
+            requests: RequestLayer::new(
+                "CurseForge",
+                TokenBucket::new(REQUESTS_BURST, REQUESTS_PER_SECOND),
+                RetryPolicy::default(),
+            ),
+            base_urls: RwLock::new(BaseUrls::default()),
+            game_version: RwLock::new(None),
+            categories: RwLock::new(None),
+        }
+    }
 
-    if !resp.status().is_success() {
-        return Err(format!("API Error: {}", resp.status()));
+    fn client(&self) -> Arc<Client> {
+        self.client.read().unwrap().clone()
     }
 
-    let json: ApiResponse<Vec<CurseForgeMod>> = resp.json()
-        .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    fn game_version(&self) -> Option<String> {
+        self.game_version.read().unwrap().clone()
+    }
 
-    Ok((json.data, json.pagination))
-}
+    fn api_url(&self) -> String {
+        BaseUrls::resolve(&self.base_urls.read().unwrap().api, CURSEFORGE_API)
+    }
+
+    pub async fn search_mods(
+        &self,
+        query: String,
+        search_sort: u32,
+        offset: u32,
+        filter: &SearchFilter,
+    ) -> Result<(Vec<CurseForgeMod>, Option<Pagination>), String> {
+        let url = format!("{}/mods/search", self.api_url());
+        let search_filter = if query.trim().is_empty() { "" } else { &query };
+
+        let mut params = vec![
+            ("gameId", HYTALE_GAME_ID.to_string()),
+            ("searchFilter", search_filter.to_string()),
+            ("pageSize", "20".to_string()),
+            ("sortField", (search_sort + 1).to_string()),
+            ("sortOrder", "desc".to_string()),
+            ("index", offset.to_string()),
+        ];
+
+        if let Some(class_id) = &filter.class_id {
+            let id = class_id.parse::<u32>().map_err(|_| format!("Invalid CurseForge class ID: {}", class_id))?;
+            params.push(("classId", id.to_string()));
+        }
+        if let Some(category_id) = &filter.category_id {
+            let id = category_id.parse::<u32>().map_err(|_| format!("Invalid CurseForge category ID: {}", category_id))?;
+            params.push(("categoryId", id.to_string()));
+        }
+        if let Some(game_version) = self.game_version() {
+            params.push(("gameVersion", game_version));
+        }
+
+        let request = self.client()
+            .get(&url)
+            .query(&params);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Network Request Failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("API Error: {}", resp.status()));
+        }
+
+        let json: ApiResponse<Vec<CurseForgeMod>> = resp.json()
+            .await
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+        Ok((json.data, json.pagination))
+    }
+
+    pub async fn get_categories(&self) -> Result<Vec<Category>, String> {
+        if let Some(cached) = self.categories.read().unwrap().as_ref() {
+            return Ok(cached.clone());
+        }
 
-pub async fn download_image(url: String) -> Result<Vec<u8>, String> {
-    let resp = client()
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        let url = format!("{}/categories", self.api_url());
 
-    if !resp.status().is_success() {
-        return Err(format!("Image download failed: {}", resp.status()));
+        let request = self.client()
+            .get(&url)
+            .query(&[("gameId", HYTALE_GAME_ID.to_string())]);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Network Request Failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("API Error: {}", resp.status()));
+        }
+
+        let json: ApiResponse<Vec<Category>> = resp.json()
+            .await
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+        if let Ok(mut lock) = self.categories.write() {
+            *lock = Some(json.data.clone());
+        }
+
+        Ok(json.data)
     }
 
-    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
-    Ok(bytes.to_vec())
-}
+    pub async fn download_image(&self, url: String) -> Result<Vec<u8>, String> {
+        let request = self.client().get(&url);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| e.to_string())?;
 
-pub async fn find_hytale_id() -> Result<u32, String> {
-    let url = format!("{}/games", CURSEFORGE_API);
-    let params = [("index", "0"), ("pageSize", "50")];
+        if !resp.status().is_success() {
+            return Err(format!("Image download failed: {}", resp.status()));
+        }
 
-    let resp = client()
-        .get(&url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+        let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+        Ok(bytes.to_vec())
+    }
+
+    pub async fn find_hytale_id(&self) -> Result<u32, String> {
+        let url = format!("{}/games", self.api_url());
+        let params = [("index", "0"), ("pageSize", "50")];
+
+        let request = self.client()
+            .get(&url)
+            .query(&params);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let json: ApiResponse<Vec<GameData>> = resp.json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if let Some(hytale) = json.data.iter().find(|g| g.name == "Hytale") {
+            Ok(hytale.id)
+        } else {
+            Err("Hytale ID not found in CurseForge response".to_string())
+        }
+    }
+
+    /// One page of a mod's files, newest first.
+    pub async fn get_mod_files_page(&self, mod_id: u32, index: u32) -> Result<(Vec<ApiFile>, Option<Pagination>), reqwest::Error> {
+        let url = format!("{}/mods/{}/files", self.api_url(), mod_id);
+
+        let request = self.client().get(&url)
+            .header("Accept", "application/json")
+            .query(&[("index", index), ("pageSize", FILES_PAGE_SIZE)]);
+        let res = self.requests.send(request)
+            .await?;
 
-    let json: ApiResponse<Vec<GameData>> = resp.json()
-        .await
-        .map_err(|e| e.to_string())?;
+        if !res.status().is_success() {
+            eprintln!("API Error: {}", res.status());
+            res.error_for_status_ref()?;
+        }
 
-    if let Some(hytale) = json.data.iter().find(|g| g.name == "Hytale") {
-        Ok(hytale.id)
-    } else {
-        Err("Hytale ID not found in CurseForge response".to_string())
+        let body: GetFilesResponse = res.json().await?;
+        Ok((body.data, body.pagination))
     }
-}
 
-pub async fn get_mod_files(mod_id: u32) -> Result<Vec<ApiFile>, reqwest::Error> {
-    let url = format!("{}/mods/{}/files?pageSize=50", CURSEFORGE_API, mod_id);
+    /// Every file of a mod, following the pagination to the end.
+    pub async fn get_mod_files(&self, mod_id: u32) -> Result<Vec<ApiFile>, reqwest::Error> {
+        let mut files = Vec::new();
+
+        loop {
+            let (page, pagination) = self.get_mod_files_page(mod_id, files.len() as u32).await?;
+            let received = page.len() as u32;
+            files.extend(page);
 
-    let res = client().get(&url)
-        .header("Accept", "application/json")
-        .send()
-        .await?;
+            let total = pagination.map(|p| p.total_count).unwrap_or(0);
+            if received == 0 || files.len() as u32 >= total || files.len() as u32 >= FILES_INDEX_LIMIT {
+                break;
+            }
+        }
 
-    if !res.status().is_success() {
-        println!("API Error: {}", res.status());
-        res.error_for_status_ref()?;
+        Ok(files)
     }
 
-    let body: GetFilesResponse = res.json().await?;
-    Ok(body.data)
-}
-pub async fn download_url(url: &str) -> Result<Vec<u8>, String> {
-    let resp = client()
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| {
-            let err = format!("Request Send Error: {}", e);
-            println!("{}", err);
+    /// Sends the download request and hands back the response unread, so
+    /// the body can be streamed. A non-zero `offset` asks for the rest of the
+    /// file only; check for `206 Partial Content` before appending.
+    pub async fn open_download(&self, url: &str, offset: u64) -> Result<Response, String> {
+        let mut request = self.client().get(url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| {
+                let err = format!("Request Send Error: {}", e);
+                eprintln!("{}", err);
+                err
+            })?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            let err = format!("Download failed: {}", status);
+            eprintln!("{}", err);
+            return Err(err);
+        }
+
+        Ok(resp)
+    }
+
+    pub async fn download_url(&self, url: &str) -> Result<Vec<u8>, String> {
+        let bytes = self.open_download(url, 0).await?.bytes().await.map_err(|e| {
+            let err = format!("Failed to read bytes: {}", e);
+            eprintln!("{}", err);
             err
         })?;
 
-    let status = resp.status();
+        Ok(bytes.to_vec())
+    }
+
+    pub async fn download_mod(&self, mod_data: &CurseForgeMod) -> Result<(String, Vec<u8>), String> {
+
+        let latest_file = mod_data.latest_files.first()
+            .ok_or_else(|| {
+                let err = "No files found for this mod".to_string();
+                eprintln!("ERROR: {}", err);
+                err
+            })?;
 
-    if !status.is_success() {
-        let err = format!("Download failed: {}", status);
-        println!("{}", err);
-        return Err(err);
+        self.download_mod_version(latest_file).await
     }
 
-    let bytes = resp.bytes().await.map_err(|e| {
-        let err = format!("Failed to read bytes: {}", e);
-        println!("{}", err);
-        err
-    })?;
+    pub async fn download_mod_version(&self, file: &ModFile) -> Result<(String, Vec<u8>), String> {
+        let url = file.download_url.as_ref()
+            .ok_or("No download URL available for this file".to_string())?;
 
-    Ok(bytes.to_vec())
-}
+        let bytes = self.download_url(url).await?;
+        UiModVersion::from_curseforge_mod_file(file).verify(&bytes)?;
+
+        Ok((file.file_name.clone(), bytes))
+    }
 
-pub async fn download_mod(mod_data: &CurseForgeMod) -> Result<(String, Vec<u8>), String> {
+    pub async fn get_mod(&self, mod_id: u32) -> Result<CurseForgeMod, String> {
+        let url = format!("{}/mods/{}", self.api_url(), mod_id);
 
-    let latest_file = mod_data.latest_files.first()
-        .ok_or_else(|| {
-            let err = "No files found for this mod".to_string();
-            println!("ERROR: {}", err);
-            err
-        })?;
+        let request = self.client().get(&url);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Network Request Failed: {}", e))?;
 
-    download_mod_version(latest_file).await
-}
+        let status = resp.status();
+        let text = resp.text().await.map_err(|e| format!("Failed to get text: {}", e))?;
+
+        if !status.is_success() {
+            return Err(format!("API Error {}: {}", status, text));
+        }
 
-pub async fn download_mod_version(file: &ModFile) -> Result<(String, Vec<u8>), String> {
-    let url = file.download_url.as_ref()
-        .ok_or("No download URL available for this file".to_string())?;
+        let json: ApiResponse<CurseForgeMod> = serde_json::from_str(&text)
+            .map_err(|e| format!("Serde Parsing Error: {} | Raw JSON: {}", e, text))?;
 
-    let bytes = download_url(url).await?;
+        Ok(json.data)
+    }
+
+    /// Several mods in one request. Unknown ids are missing from the result.
+    pub async fn get_mods(&self, mod_ids: &[u32]) -> Result<Vec<CurseForgeMod>, String> {
+        let url = format!("{}/mods", self.api_url());
+        let mut mods = Vec::with_capacity(mod_ids.len());
+
+        for chunk in mod_ids.chunks(MODS_BATCH_SIZE) {
+            let request = self.client()
+                .post(&url)
+                .json(&GetModsRequest { mod_ids: chunk, filter_pc_only: false });
+            let resp = self.requests.send(request)
+                .await
+                .map_err(|e| format!("Network Request Failed: {}", e))?;
+
+            if !resp.status().is_success() {
+                return Err(format!("API Error: {}", resp.status()));
+            }
+
+            let json: ApiResponse<Vec<CurseForgeMod>> = resp.json()
+                .await
+                .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+            mods.extend(json.data);
+        }
+
+        Ok(mods)
+    }
 
-    Ok((file.file_name.clone(), bytes))
+    /// `latestFiles` only holds the newest few files, which may all target a
+    /// newer client than the one configured; look further back in that case.
+    async fn with_compatible_version(&self, mut ui_mod: UiMod, mod_id: u32) -> Result<UiMod, String> {
+        let game_version = self.game_version();
+        if ui_mod.version.file_id.is_empty() && game_version.is_some() {
+            let files = self.get_mod_files(mod_id).await.map_err(|e| e.to_string())?;
+            let versions = files.iter().map(UiModVersion::from_curseforge_api_file);
+            if let Some(version) = pick_version(versions, game_version.as_deref()) {
+                ui_mod.version = version;
+            }
+        }
+        Ok(ui_mod)
+    }
 }
 
-pub async fn get_mod(mod_id: u32) -> Result<CurseForgeMod, String> {
-    let url = format!("{}/mods/{}", CURSEFORGE_API, mod_id);
+#[async_trait]
+impl ModProvider for CurseForgeProvider {
+    fn id(&self) -> ApiProvider {
+        ApiProvider::CurseForge
+    }
+
+    fn set_api_key(&self, key: &str) {
+        let new_client = Arc::new(build_client(Some(key)));
+
+        if let Ok(mut lock) = self.client.write() {
+            *lock = new_client;
+            eprintln!("API key applied to all future requests on Curse Forge.");
+        }
+    }
+
+    fn set_base_urls(&self, urls: &BaseUrls) {
+        if let Ok(mut lock) = self.base_urls.write() {
+            *lock = urls.clone();
+        }
+        if let Ok(mut lock) = self.categories.write() {
+            *lock = None;
+        }
+    }
+
+    fn set_game_version(&self, version: Option<&str>) {
+        if let Ok(mut lock) = self.game_version.write() {
+            *lock = version.map(str::to_string);
+        }
+    }
+
+    fn requires_api_key(&self) -> bool {
+        true
+    }
+
+    fn supports_categories(&self) -> bool {
+        true
+    }
+
+    async fn search(
+        &self,
+        query: String,
+        sort: u32,
+        offset: u32,
+        filter: &SearchFilter,
+    ) -> Result<(Vec<UiMod>, u32), String> {
+        let (api_mods, pagination) = self.search_mods(query, sort, offset, filter).await?;
+        let game_version = self.game_version();
+
+        let ui_mods = api_mods.iter()
+            .map(|m| UiMod::from_curseforge_api(m, game_version.as_deref()))
+            .collect();
+
+        let total_pages = if let Some(p) = pagination {
+            (p.total_count as f64 / p.page_size as f64).ceil() as u32
+        } else {
+            0
+        };
+
+        Ok((ui_mods, total_pages))
+    }
+
+    async fn categories(&self) -> Result<Vec<UiCategory>, String> {
+        let categories = self.get_categories().await?;
+        Ok(categories.iter().map(UiCategory::from_curseforge_api).collect())
+    }
+
+    async fn get_versions(&self, mod_id: &str) -> Result<Vec<UiModVersion>, String> {
+        let cf_id = mod_id.parse::<u32>()
+            .map_err(|_| "Invalid ID format for CurseForge (expected number)".to_string())?;
+
+        match self.get_mod_files(cf_id).await {
+            Ok(files) => Ok(files.iter()
+                .map(UiModVersion::from_curseforge_api_file)
+                .collect()),
+            Err(e) => {
+                eprintln!("CurseForge Versions fetch FAILED: {}", e);
+                Err(e.to_string())
+            }
+        }
+    }
+
+    async fn get_versions_page(&self, mod_id: &str, offset: u32) -> Result<VersionPage, String> {
+        let cf_id = mod_id.parse::<u32>()
+            .map_err(|_| "Invalid ID format for CurseForge (expected number)".to_string())?;
+
+        let (files, pagination) = self.get_mod_files_page(cf_id, offset).await.map_err(|e| {
+            eprintln!("CurseForge Versions fetch FAILED: {}", e);
+            e.to_string()
+        })?;
+
+        let versions: Vec<_> = files.iter().map(UiModVersion::from_curseforge_api_file).collect();
+        let total_count = pagination.map(|p| p.total_count);
+        let loaded = offset + versions.len() as u32;
 
-    let resp = client()
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Network Request Failed: {}", e))?;
+        Ok(VersionPage {
+            has_more: !versions.is_empty() && total_count.is_some_and(|t| loaded < t) && loaded < FILES_INDEX_LIMIT,
+            versions,
+            offset,
+            total_count,
+        })
+    }
 
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| format!("Failed to get text: {}", e))?;
+    async fn get_details(&self, mod_id: &str) -> Result<UiMod, String> {
+        let id_num = mod_id.parse::<u32>()
+            .map_err(|_| format!("Invalid CurseForge ID: {}", mod_id))?;
 
-    if !status.is_success() {
-        return Err(format!("API Error {}: {}", status, text));
+        let game_version = self.game_version();
+        let ui_mod = UiMod::from_curseforge_api(&self.get_mod(id_num).await?, game_version.as_deref());
+        self.with_compatible_version(ui_mod, id_num).await
     }
 
-    let json: ApiResponse<CurseForgeMod> = serde_json::from_str(&text)
-        .map_err(|e| format!("Serde Parsing Error: {} | Raw JSON: {}", e, text))?;
+    async fn get_details_batch(&self, mod_ids: &[String]) -> Result<Vec<UiMod>, String> {
+        let ids: Vec<u32> = mod_ids.iter()
+            .filter_map(|id| match id.parse::<u32>() {
+                Ok(n) => Some(n),
+                Err(_) => {
+                    eprintln!("Skipping invalid CurseForge ID: {}", id);
+                    None
+                }
+            })
+            .collect();
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let game_version = self.game_version();
+        let api_mods = self.get_mods(&ids).await?;
+
+        // A failed file lookup only costs that mod its compatible version,
+        // not the whole batch.
+        let ui_mods = stream::iter(api_mods)
+            .map(|api_mod| {
+                let ui_mod = UiMod::from_curseforge_api(&api_mod, game_version.as_deref());
+                async move {
+                    match self.with_compatible_version(ui_mod.clone(), api_mod.id).await {
+                        Ok(ui_mod) => ui_mod,
+                        Err(e) => {
+                            eprintln!("CurseForge file lookup FAILED for {}: {}", api_mod.id, e);
+                            ui_mod
+                        }
+                    }
+                }
+            })
+            .buffered(DETAILS_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(ui_mods)
+    }
 
-    Ok(json.data)
-}
\ No newline at end of file
+    async fn open_download(&self, url: &str, offset: u64) -> Result<Response, String> {
+        CurseForgeProvider::open_download(self, url, offset).await
+    }
+}
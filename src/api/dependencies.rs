@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+use futures::future::join_all;
+use serde::Serialize;
+use crate::api::manual::download_page;
+use crate::api::queue::{InstallQueue, JobId};
+use crate::api::settings::AppSettings;
+use crate::api::ui_mod::{get_mod_details_unified, UiMod, UiModVersion};
+
+/// One mod a plan downloads.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedInstall {
+    #[serde(rename = "mod")]
+    pub ui_mod: UiMod,
+    pub version: UiModVersion,
+    /// Name of the mod that needs this one, `None` for the mod being installed.
+    pub required_by: Option<String>,
+}
+
+/// A requirement already met by an installed mod.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SatisfiedDependency {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub version_name: String,
+    pub required_by: String,
+}
+
+/// A requirement the plan can't meet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MissingDependency {
+    pub mod_id: String,
+    pub required_by: String,
+    pub reason: String,
+}
+
+/// Two mods marked incompatible with each other by their provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub other_mod_id: String,
+    pub other_mod_name: String,
+}
+
+/// Everything installing one mod involves, worked out before anything is
+/// downloaded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InstallPlan {
+    /// The mod being installed first, then its dependencies in the order
+    /// they were found.
+    pub installs: Vec<PlannedInstall>,
+    pub satisfied: Vec<SatisfiedDependency>,
+    pub missing: Vec<MissingDependency>,
+    /// Planned mods that clash with each other or with installed ones.
+    pub conflicts: Vec<Conflict>,
+    /// The target replaces an installed version, so it keeps its dependency
+    /// mark; see [`InstallQueue::enqueue_update`].
+    #[serde(skip)]
+    pub update: bool,
+}
+
+impl InstallPlan {
+    /// A plan for `ui_mod` alone, ignoring whatever it requires.
+    pub fn single(ui_mod: &UiMod, version: UiModVersion) -> Self {
+        Self {
+            installs: vec![PlannedInstall { ui_mod: ui_mod.clone(), version, required_by: None }],
+            satisfied: vec![],
+            missing: vec![],
+            conflicts: vec![],
+            update: false,
+        }
+    }
+
+    pub fn target(&self) -> &PlannedInstall {
+        &self.installs[0]
+    }
+
+    /// The mods installed only because the target needs them.
+    pub fn dependencies(&self) -> &[PlannedInstall] {
+        &self.installs[1..]
+    }
+
+    /// Whether the plan involves anything besides the target itself.
+    pub fn has_dependencies(&self) -> bool {
+        self.installs.len() > 1 || !self.satisfied.is_empty() || !self.missing.is_empty()
+    }
+
+    /// Whether the user should see the plan before it runs.
+    pub fn needs_review(&self) -> bool {
+        self.has_dependencies() || !self.conflicts.is_empty()
+    }
+
+    /// Fills in `conflicts` from the incompatibilities the planned files
+    /// declare and those recorded for enabled installed mods. An installed mod
+    /// the plan replaces is not checked against its replacement.
+    pub fn check_conflicts(&mut self, settings: &AppSettings) {
+        let mut conflicts = Vec::new();
+        for (index, planned) in self.installs.iter().enumerate() {
+            let incompatible = planned.version.incompatible_mod_ids();
+
+            for entry in settings.installed_mods.values() {
+                if entry.disabled
+                    || entry.api_provider != planned.ui_mod.provider
+                    || self.installs.iter().any(|p| p.ui_mod.id == entry.mod_id) {
+                    continue;
+                }
+                if incompatible.contains(&entry.mod_id) || entry.incompatible.contains(&planned.ui_mod.id) {
+                    conflicts.push(Conflict {
+                        mod_id: planned.ui_mod.id.clone(),
+                        mod_name: planned.ui_mod.name.clone(),
+                        other_mod_id: entry.mod_id.clone(),
+                        other_mod_name: entry.mod_name.clone(),
+                    });
+                }
+            }
+
+            for other in &self.installs[index + 1..] {
+                if incompatible.contains(&other.ui_mod.id)
+                    || other.version.incompatible_mod_ids().contains(&planned.ui_mod.id) {
+                    conflicts.push(Conflict {
+                        mod_id: planned.ui_mod.id.clone(),
+                        mod_name: planned.ui_mod.name.clone(),
+                        other_mod_id: other.ui_mod.id.clone(),
+                        other_mod_name: other.ui_mod.name.clone(),
+                    });
+                }
+            }
+        }
+        self.conflicts = conflicts;
+    }
+
+    /// Queues every install in the plan, the target first.
+    pub fn enqueue(&self, queue: &InstallQueue) -> Vec<JobId> {
+        self.installs.iter()
+            .map(|p| match &p.required_by {
+                Some(required_by) => queue.enqueue_dependency(&p.ui_mod, p.version.clone(), required_by),
+                None if self.update => queue.enqueue_update(&p.ui_mod, p.version.clone()),
+                None => queue.enqueue(&p.ui_mod, p.version.clone()),
+            })
+            .collect()
+    }
+}
+
+fn requirements(planned: &PlannedInstall) -> Vec<(String, String)> {
+    planned.version.required_mod_ids()
+        .into_iter()
+        .map(|mod_id| (mod_id, planned.ui_mod.name.clone()))
+        .collect()
+}
+
+/// Follows the required dependencies of `version` until every one is either
+/// installed already, planned, or known to be missing. Dependencies live on
+/// the same provider as the mod that needs them, and the provider picks their
+/// newest file for the target game version, just as it does for search
+/// results. Each level of the tree is looked up concurrently, and the finished
+/// plan is checked for conflicts.
+pub async fn resolve_install_plan(ui_mod: &UiMod, version: UiModVersion, settings: &AppSettings) -> InstallPlan {
+    let api_provider = ui_mod.provider.clone();
+    let mut plan = InstallPlan::single(ui_mod, version);
+    let mut seen = HashSet::from([ui_mod.id.clone()]);
+    let mut level = requirements(plan.target());
+
+    while !level.is_empty() {
+        let mut lookups = Vec::new();
+        for (mod_id, required_by) in level {
+            if !seen.insert(mod_id.clone()) {
+                continue;
+            }
+
+            let installed = settings.installed_mods.values()
+                .find(|e| e.api_provider == api_provider && e.mod_id == mod_id);
+            match installed {
+                // The game doesn't load a disabled mod, so it meets nothing.
+                Some(entry) if entry.disabled => plan.missing.push(MissingDependency {
+                    mod_id,
+                    required_by,
+                    reason: format!("{} is installed but disabled", entry.mod_name),
+                }),
+                Some(entry) => plan.satisfied.push(SatisfiedDependency {
+                    mod_id,
+                    mod_name: entry.mod_name.clone(),
+                    version_name: entry.version_name.clone(),
+                    required_by,
+                }),
+                None => lookups.push((mod_id, required_by)),
+            }
+        }
+
+        let details = join_all(lookups.iter().map(|(mod_id, _)| get_mod_details_unified(&api_provider, mod_id))).await;
+
+        level = Vec::new();
+        for ((mod_id, required_by), found) in lookups.into_iter().zip(details) {
+            let reason = match &found {
+                None => Some(format!("Not found on {}", api_provider.display_name())),
+                Some(dep) if dep.version.file_id.is_empty() => Some("No file for the target game version".to_string()),
+                Some(dep) if dep.needs_manual_download(&dep.version) => Some(format!(
+                    "Only downloadable from {}: {}",
+                    api_provider.display_name(),
+                    download_page(dep, &dep.version),
+                )),
+                Some(_) => None,
+            };
+
+            match (found, reason) {
+                (Some(dep), None) => {
+                    let planned = PlannedInstall {
+                        version: dep.version.clone(),
+                        ui_mod: dep,
+                        required_by: Some(required_by),
+                    };
+                    level.extend(requirements(&planned));
+                    plan.installs.push(planned);
+                }
+                (_, reason) => plan.missing.push(MissingDependency {
+                    mod_id,
+                    required_by,
+                    reason: reason.unwrap_or_default(),
+                }),
+            }
+        }
+    }
+
+    plan.check_conflicts(settings);
+    plan
+}
+
+/// Pairs of enabled mods that are marked incompatible with each other, each
+/// pair listed once.
+pub fn audit_conflicts(settings: &AppSettings) -> Vec<Conflict> {
+    let mut entries: Vec<_> = settings.installed_mods.values().filter(|e| !e.disabled).collect();
+    entries.sort_by_key(|e| e.mod_name.to_lowercase());
+
+    let mut conflicts = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        for other in &entries[index + 1..] {
+            if entry.conflicts_with(other) {
+                conflicts.push(Conflict {
+                    mod_id: entry.mod_id.clone(),
+                    mod_name: entry.mod_name.clone(),
+                    other_mod_id: other.mod_id.clone(),
+                    other_mod_name: other.mod_name.clone(),
+                });
+            }
+        }
+    }
+    conflicts
+}
@@ -0,0 +1,237 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use reqwest::header::CONTENT_RANGE;
+use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::watch;
+use crate::api::checksum::{FileChecksum, Verifier};
+use crate::api::ui_mod::UiModVersion;
+
+/// Bytes received so far for one download, against the size the provider
+/// reported (or the server's `Content-Length` when the provider gave none).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct DownloadProgress {
+    pub received: u64,
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Share of the file received, between 0 and 1, when the size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total
+            .filter(|t| *t > 0)
+            .map(|t| (self.received as f64 / t as f64).min(1.0))
+    }
+}
+
+/// How many times one download picks up again after the connection drops.
+pub const RESUME_ATTEMPTS: u32 = 3;
+
+pub type ProgressSender = watch::Sender<DownloadProgress>;
+pub type ProgressReceiver = watch::Receiver<DownloadProgress>;
+
+pub fn progress_channel() -> (ProgressSender, ProgressReceiver) {
+    watch::channel(DownloadProgress::default())
+}
+
+/// Where partial downloads live. It sits next to `Mods` so finished files
+/// can be renamed into place, and the game never sees a half-written jar.
+pub fn get_downloads_dir(hytale_folder: &Path) -> PathBuf {
+    hytale_folder.join("UserData").join(".downloads")
+}
+
+/// A complete file waiting in the downloads folder. Dropping it without
+/// installing deletes the file.
+#[derive(Debug)]
+pub struct DownloadedFile {
+    pub file_name: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl Drop for DownloadedFile {
+    fn drop(&mut self) {
+        if self.path.exists() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// A download whose bytes are all on disk but not yet checked.
+pub struct StreamedFile {
+    pub file: DownloadedFile,
+    verifier: Verifier,
+}
+
+impl StreamedFile {
+    /// Compares what was received against the version's size and checksums,
+    /// deleting the file if they disagree.
+    pub fn verify(self, version: &UiModVersion) -> Result<DownloadedFile, String> {
+        self.verifier.finish(version.file_size)
+            .map_err(|e| format!("{} failed verification: {}", version.file_name, e))?;
+        Ok(self.file)
+    }
+}
+
+/// What a partial download in the staging folder was meant to become,
+/// stored beside it so a later attempt knows whether it can pick up where
+/// the last one stopped.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct PartialMeta {
+    file_id: String,
+    size: Option<u64>,
+    checksums: Vec<FileChecksum>,
+}
+
+impl PartialMeta {
+    fn for_version(version: &UiModVersion) -> Self {
+        Self {
+            file_id: version.file_id.clone(),
+            size: version.file_size,
+            checksums: version.checksums.clone(),
+        }
+    }
+}
+
+pub fn partial_path(hytale_folder: &Path, version: &UiModVersion) -> PathBuf {
+    get_downloads_dir(hytale_folder).join(format!("{}.part", version.file_name))
+}
+
+fn meta_path(part: &Path) -> PathBuf {
+    let mut name = part.as_os_str().to_owned();
+    name.push(".json");
+    PathBuf::from(name)
+}
+
+/// Bytes already in the staging folder from an earlier attempt at this exact
+/// file. A partial left by some other file, or one that is already as long
+/// as the file should be, is thrown away and the download starts over.
+pub fn resume_offset(hytale_folder: &Path, version: &UiModVersion) -> u64 {
+    let part = partial_path(hytale_folder, version);
+    let meta = meta_path(&part);
+
+    let matches = fs::read_to_string(&meta)
+        .ok()
+        .and_then(|text| serde_json::from_str::<PartialMeta>(&text).ok())
+        .is_some_and(|m| m == PartialMeta::for_version(version));
+    let length = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+    if matches && length > 0 && version.file_size.is_none_or(|size| length < size) {
+        return length;
+    }
+
+    let _ = fs::remove_file(&part);
+    let _ = fs::remove_file(&meta);
+    0
+}
+
+/// Start of the range in a `Content-Range: bytes <start>-<end>/<total>` header.
+fn content_range_start(resp: &Response) -> Option<u64> {
+    resp.headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Streams `resp` into the downloads folder, hashing as it goes so the file
+/// never has to be held in memory. When `offset` bytes are already staged and
+/// the server honoured the range request, the response is appended to them;
+/// otherwise the file is written from the start. If the transfer breaks off,
+/// the partial file stays behind for `resume_offset` to pick up.
+pub async fn stream_to_file(
+    mut resp: Response,
+    hytale_folder: &Path,
+    version: &UiModVersion,
+    offset: u64,
+    progress: Option<&ProgressSender>,
+) -> Result<StreamedFile, String> {
+    let dir = get_downloads_dir(hytale_folder);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let part = partial_path(hytale_folder, version);
+    let meta = meta_path(&part);
+    let resuming = offset > 0
+        && resp.status() == StatusCode::PARTIAL_CONTENT
+        && content_range_start(&resp) == Some(offset);
+
+    let mut verifier = Verifier::new(&version.checksums);
+    let mut file = if resuming {
+        // The checksums cover the whole file, so what is already on disk
+        // goes through the verifier first.
+        let mut existing = tokio::fs::File::open(&part)
+            .await
+            .map_err(|e| format!("Failed to reopen {}: {}", part.display(), e))?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let n = existing.read(&mut buffer).await.map_err(|e| e.to_string())?;
+            if n == 0 {
+                break;
+            }
+            verifier.update(&buffer[..n]);
+        }
+
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(&part)
+            .await
+            .map_err(|e| format!("Failed to reopen {}: {}", part.display(), e))?
+    } else {
+        let json = serde_json::to_string(&PartialMeta::for_version(version)).map_err(|e| e.to_string())?;
+        fs::write(&meta, json).map_err(|e| e.to_string())?;
+
+        tokio::fs::File::create(&part)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", part.display(), e))?
+    };
+
+    let total = version.file_size
+        .filter(|s| *s > 0)
+        .or(resp.content_length().map(|len| len + verifier.received()));
+    if let Some(tx) = progress {
+        tx.send_replace(DownloadProgress { received: verifier.received(), total });
+    }
+
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("Failed to read bytes: {}", e))? {
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        verifier.update(&chunk);
+
+        if let Some(tx) = progress {
+            tx.send_replace(DownloadProgress { received: verifier.received(), total });
+        }
+    }
+
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    let _ = fs::remove_file(&meta);
+
+    let download = DownloadedFile {
+        file_name: version.file_name.clone(),
+        path: part,
+        size: verifier.received(),
+    };
+    Ok(StreamedFile { file: download, verifier })
+}
+
+/// Human-readable size, e.g. `1.4 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,126 @@
+use futures::future::join_all;
+use std::collections::HashSet;
+use std::sync::Arc;
+use crate::api::provider::{registry, ModProvider};
+use crate::api::settings::AppSettings;
+use crate::api::ui_mod::{SearchFilter, UiMod};
+
+/// Providers that can be queried with the current settings: either the key is
+/// optional or one has been stored.
+pub fn configured_providers(settings: &AppSettings) -> Vec<Arc<dyn ModProvider>> {
+    let mut providers: Vec<_> = registry().all()
+        .filter(|p| {
+            !p.requires_api_key() || settings.get_api_key_for(&p.id()).is_some_and(|k| !k.is_empty())
+        })
+        .cloned()
+        .collect();
+
+    // Keep the active provider first so it wins ties when ranking.
+    providers.sort_by_key(|p| p.id() != settings.api_provider);
+    providers
+}
+
+/// Queries every configured provider at once and merges the ranked results,
+/// folding likely duplicates into a single entry. With a category filter set,
+/// providers that cannot apply it are left out rather than returning
+/// unfiltered results.
+pub async fn search_mods_federated(
+    settings: &AppSettings,
+    sort: u32,
+    query: String,
+    offset: u32,
+    filter: &SearchFilter,
+) -> Result<(Vec<UiMod>, u32), String> {
+    let providers = configured_providers(settings);
+    if providers.is_empty() {
+        return Err("No provider is configured".to_string());
+    }
+
+    let providers: Vec<_> = providers.into_iter()
+        .filter(|p| filter.is_empty() || p.supports_categories())
+        .collect();
+    if providers.is_empty() {
+        return Err("No configured provider supports category filters".to_string());
+    }
+
+    let results = join_all(providers.iter().map(|p| p.search(query.clone(), sort, offset, filter))).await;
+
+    let mut lists = Vec::new();
+    let mut errors = Vec::new();
+    let mut total_pages = 0;
+
+    for (provider, result) in providers.iter().zip(results) {
+        match result {
+            Ok((mods, pages)) => {
+                total_pages = total_pages.max(pages);
+                lists.push(mods);
+            }
+            Err(e) => {
+                eprintln!("{:?} federated search FAILED: {}", provider.id(), e);
+                errors.push(format!("{:?}: {}", provider.id(), e));
+            }
+        }
+    }
+
+    if lists.is_empty() {
+        return Err(errors.join("; "));
+    }
+
+    Ok((merge_ranked(lists), total_pages))
+}
+
+/// Interleaves the lists by rank, so the n-th result of every provider comes
+/// before the (n+1)-th of any, and merges duplicates into the earlier entry
+/// unless that entry already has a source on the duplicate's provider.
+pub fn merge_ranked(lists: Vec<Vec<UiMod>>) -> Vec<UiMod> {
+    let longest = lists.iter().map(|l| l.len()).max().unwrap_or(0);
+    let mut iters: Vec<_> = lists.into_iter().map(|l| l.into_iter()).collect();
+    let mut merged: Vec<UiMod> = Vec::new();
+
+    for _ in 0..longest {
+        for iter in iters.iter_mut() {
+            let Some(candidate) = iter.next() else { continue };
+
+            // An entry already carrying this provider is a different project
+            // on that provider, not another source for the same one.
+            let existing = merged.iter_mut()
+                .find(|m| !m.providers().contains(&candidate.provider) && is_likely_duplicate(m, &candidate));
+            match existing {
+                Some(existing) => existing.other_sources.push(candidate.as_source()),
+                None => merged.push(candidate),
+            }
+        }
+    }
+
+    merged
+}
+
+fn normalize(value: &str) -> String {
+    value.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn author_set(authors: &str) -> HashSet<String> {
+    authors.split(',')
+        .map(normalize)
+        .filter(|a| !a.is_empty())
+        .collect()
+}
+
+/// Two mods are the same project when the name or slug matches and either the
+/// authors overlap or both name and slug match.
+pub fn is_likely_duplicate(a: &UiMod, b: &UiMod) -> bool {
+    let name_a = normalize(&a.name);
+    let slug_a = normalize(&a.slug);
+    let name_match = !name_a.is_empty() && name_a == normalize(&b.name);
+    let slug_match = !slug_a.is_empty() && slug_a == normalize(&b.slug);
+
+    if !name_match && !slug_match {
+        return false;
+    }
+
+    let author_match = !author_set(&a.authors).is_disjoint(&author_set(&b.authors));
+    author_match || (name_match && slug_match)
+}
@@ -0,0 +1,185 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// How often, and how patiently, a failed request is tried again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included.
+    pub max_attempts: u32,
+    /// Wait before the first retry; each later retry waits twice as long.
+    pub base_delay: Duration,
+    /// Longest single wait. A `Retry-After` asking for more is not waited out.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry`, counting from zero.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << retry.min(16))
+            .min(self.max_delay)
+    }
+}
+
+/// Statuses worth another try: rate limiting and server-side failures.
+pub fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+/// `Retry-After` in its delay-seconds form. The HTTP-date form is ignored
+/// and the regular backoff used instead.
+pub fn retry_after(resp: &Response) -> Option<Duration> {
+    resp.headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+struct Bucket {
+    tokens: f64,
+    /// When `tokens` was last topped up. Pushed into the future to hold
+    /// every request back after the server asks for a pause.
+    refilled_at: Instant,
+}
+
+/// Allows bursts of up to `capacity` requests, refilling at `per_second`.
+pub struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    state: Mutex<Bucket>,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, per_second: f64) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            per_second: per_second.max(f64::MIN_POSITIVE),
+            state: Mutex::new(Bucket { tokens: capacity, refilled_at: Instant::now() }),
+        }
+    }
+
+    /// Takes a token if one is free, otherwise says how long until one is.
+    fn try_take(&self) -> Result<(), Duration> {
+        let mut bucket = self.state.lock().unwrap();
+        let now = Instant::now();
+        if now < bucket.refilled_at {
+            return Err(bucket.refilled_at - now);
+        }
+
+        let elapsed = (now - bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second))
+        }
+    }
+
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_take() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Holds back every request for `duration`, then refills from empty.
+    pub fn pause(&self, duration: Duration) {
+        let mut bucket = self.state.lock().unwrap();
+        let until = Instant::now() + duration;
+        if until > bucket.refilled_at {
+            bucket.tokens = 0.0;
+            bucket.refilled_at = until;
+        }
+    }
+}
+
+/// Sends a provider's requests through its rate limiter, retrying network
+/// errors, `429 Too Many Requests` and 5xx responses with exponential
+/// backoff. A `Retry-After` from the server replaces the backoff, and on a
+/// 429 holds back the provider's other requests too.
+pub struct RequestLayer {
+    label: &'static str,
+    bucket: TokenBucket,
+    policy: Mutex<RetryPolicy>,
+}
+
+impl RequestLayer {
+    pub fn new(label: &'static str, bucket: TokenBucket, policy: RetryPolicy) -> Self {
+        Self { label, bucket, policy: Mutex::new(policy) }
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        *self.policy.lock().unwrap()
+    }
+
+    pub fn set_policy(&self, policy: RetryPolicy) {
+        *self.policy.lock().unwrap() = policy;
+    }
+
+    /// Sends `request`, retrying per the policy. Once retries run out the
+    /// last response is returned as-is, so callers still check the status.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let policy = self.policy();
+        let mut attempt = 1;
+
+        loop {
+            // Streaming bodies can't be replayed, so they get a single try.
+            let Some(current) = request.try_clone() else {
+                self.bucket.acquire().await;
+                return request.send().await;
+            };
+
+            self.bucket.acquire().await;
+            let wait = match current.send().await {
+                Ok(resp) if !is_retryable(resp.status()) => return Ok(resp),
+                Ok(resp) => {
+                    let status = resp.status();
+                    let wait = retry_after(&resp).unwrap_or_else(|| policy.backoff(attempt - 1));
+                    if attempt >= policy.max_attempts || wait > policy.max_delay {
+                        eprintln!("{} answered {}, giving up after {} attempt(s)", self.label, status, attempt);
+                        return Ok(resp);
+                    }
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        self.bucket.pause(wait);
+                    }
+                    eprintln!("{} answered {}, retrying in {:?}", self.label, status, wait);
+                    wait
+                }
+                Err(e) if attempt < policy.max_attempts && is_transient(&e) => {
+                    let wait = policy.backoff(attempt - 1);
+                    eprintln!("{} request failed ({}), retrying in {:?}", self.label, e, wait);
+                    wait
+                }
+                Err(e) => return Err(e),
+            };
+
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+}
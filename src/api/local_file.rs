@@ -0,0 +1,149 @@
+//! Mods installed from a jar or zip already on disk, such as a private build,
+//! instead of being downloaded from a provider. They are recorded under the
+//! `Local` provider with whatever the archive says about itself.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use serde::Serialize;
+use serde_json::Value;
+use crate::api::checksum::HashAlgo;
+use crate::api::local_mods::{extract_base_name, install_local_file};
+use crate::api::routing::ContentType;
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
+
+/// Manifest at the root of a Hytale plugin archive.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// What could be learned about an archive before installing it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocalModInfo {
+    pub file_name: String,
+    /// `Group.Name` from the manifest, or the file's base name.
+    pub mod_id: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    /// Ids of the plugins the manifest requires, in the same form as `mod_id`.
+    pub dependencies: Vec<String>,
+}
+
+/// Looks up `key` in a manifest object, ignoring case, since plugin authors
+/// don't all capitalise the same way.
+fn field<'a>(manifest: &'a Value, key: &str) -> Option<&'a Value> {
+    manifest.as_object()?
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+}
+
+fn text(manifest: &Value, key: &str) -> Option<String> {
+    field(manifest, key)?.as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// `Group:Name` plugin references become ids that are safe to use as a
+/// folder name. `None` for anything that could name another path, since the
+/// manifest comes from an untrusted archive.
+fn plugin_id(reference: &str) -> Option<String> {
+    let id = reference.trim().replace(':', ".");
+    let unsafe_id = id.is_empty() || id.contains("..") || id.contains(['/', '\\', '\0']);
+    (!unsafe_id).then_some(id)
+}
+
+fn read_manifest(path: &Path) -> Result<Option<Value>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("{} is not a valid archive: {}", path.display(), e))?;
+
+    let mut entry = match archive.by_name(MANIFEST_FILE) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", MANIFEST_FILE, e)),
+    };
+    let mut json = String::new();
+    entry.read_to_string(&mut json).map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+
+    // A broken manifest shouldn't stop the install; the file name still says enough.
+    match serde_json::from_str(&json) {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(e) => {
+            eprintln!("Ignoring invalid {} in {}: {}", MANIFEST_FILE, path.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// Reads the metadata of a jar or zip, falling back to what the file name
+/// says for anything the manifest leaves out.
+pub fn read_local_mod(path: &Path) -> Result<LocalModInfo, String> {
+    let file_name = path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("{} is not a file", path.display()))?;
+    if !path.extension().is_some_and(|ext| ext == "jar" || ext == "zip") {
+        return Err(format!("{} is not a .jar or .zip file", file_name));
+    }
+
+    let manifest = read_manifest(path)?.unwrap_or(Value::Null);
+    let (base_name, file_version) = extract_base_name(&file_name);
+
+    let name = text(&manifest, "Name");
+    let mod_id = match (text(&manifest, "Group"), &name) {
+        (Some(group), Some(name)) => plugin_id(&format!("{}:{}", group, name)),
+        (None, Some(name)) => plugin_id(name),
+        _ => None,
+    }.unwrap_or_else(|| base_name.to_lowercase());
+    let authors = field(&manifest, "Authors")
+        .and_then(Value::as_array)
+        .map(|authors| authors.iter()
+            .filter_map(|a| a.as_str().map(str::to_string).or_else(|| text(a, "Name")))
+            .collect())
+        .unwrap_or_default();
+    let mut dependencies: Vec<String> = field(&manifest, "Dependencies")
+        .and_then(Value::as_object)
+        .map(|deps| deps.keys().filter_map(|k| plugin_id(k)).collect())
+        .unwrap_or_default();
+    dependencies.sort();
+
+    Ok(LocalModInfo {
+        name: name.unwrap_or_else(|| base_name.replace('-', " ")),
+        version: text(&manifest, "Version").unwrap_or(file_version),
+        description: text(&manifest, "Description"),
+        file_name,
+        mod_id,
+        authors,
+        dependencies,
+    })
+}
+
+/// Copies a jar or zip into the game and records it under the `Local`
+/// provider. A file with the same mod id replaces the installed one, like an
+/// update. Returns what was read from the archive.
+pub fn install_from_file(
+    hytale_folder: &Path,
+    path: &Path,
+    content_type: ContentType,
+    settings: &mut AppSettings,
+) -> Result<LocalModInfo, String> {
+    let info = read_local_mod(path)?;
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let entry = InstalledModEntry {
+        mod_id: info.mod_id.clone(),
+        mod_name: info.name.clone(),
+        // The content hash tells builds apart when the version string doesn't.
+        file_id: HashAlgo::Sha1.digest(&data)[..12].to_string(),
+        version_name: info.version.clone(),
+        api_provider: ApiProvider::Local,
+        dependencies: info.dependencies.clone(),
+        is_dependency: false,
+        incompatible: vec![],
+        disabled: false,
+        content_type,
+    };
+    install_local_file(hytale_folder, path, &info.file_name, entry, settings)?;
+    Ok(info)
+}
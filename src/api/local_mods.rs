@@ -1,9 +1,52 @@
+use std::collections::HashSet;
 use std::fs;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use crate::api::archive::{archive_file, archived_versions, ArchivedVersion};
+use crate::api::download::{get_downloads_dir, DownloadedFile};
+use crate::api::routing::{ContentType, RoutingTable};
 use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
 
-fn get_mods_dir(hytale_folder: &Path) -> PathBuf {
-    hytale_folder.join("UserData").join("Mods")
+/// Where mods go under `routes`; other resources have their own folders.
+pub fn get_mods_dir(hytale_folder: &Path, routes: &RoutingTable) -> PathBuf {
+    routes.dir(hytale_folder, ContentType::Mod)
+}
+
+/// Where disabled mods are kept. It sits beside `Mods`, out of the game's
+/// sight, so a mod can be switched back on without downloading it again.
+pub fn get_disabled_dir(hytale_folder: &Path) -> PathBuf {
+    hytale_folder.join("UserData").join(".disabled")
+}
+
+/// Where an installed file currently lives, depending on its content type and
+/// whether it is enabled. Files the manifest doesn't know are taken for mods.
+pub fn installed_path(hytale_folder: &Path, file_name: &str, entry: Option<&InstalledModEntry>, routes: &RoutingTable) -> PathBuf {
+    match entry {
+        Some(entry) if entry.disabled => get_disabled_dir(hytale_folder).join(file_name),
+        Some(entry) => routes.dir(hytale_folder, entry.content_type).join(file_name),
+        None => get_mods_dir(hytale_folder, routes).join(file_name),
+    }
+}
+
+/// File names of everything installed: the archives in every routed folder,
+/// plus the disabled files the manifest tracks. Sorted by name.
+pub fn scan_installed(hytale_folder: &Path, settings: &AppSettings) -> Vec<String> {
+    let mut files: Vec<String> = settings.routes.dirs(hytale_folder).iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "jar" || ext == "zip"))
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+
+    // Disabled mods wait in the holding folder but are still installed.
+    let disabled_dir = get_disabled_dir(hytale_folder);
+    files.extend(settings.installed_mods.iter()
+        .filter(|(file_name, entry)| entry.disabled && disabled_dir.join(file_name).is_file())
+        .map(|(file_name, _)| file_name.clone()));
+
+    files.sort_by_key(|f| f.to_lowercase());
+    files.dedup();
+    files
 }
 
 pub fn extract_base_name(filename: &str) -> (String, String) {
@@ -33,7 +76,8 @@ pub fn extract_base_name(filename: &str) -> (String, String) {
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum InstallStatus {
     Installed,
     #[default]
@@ -41,7 +85,7 @@ pub enum InstallStatus {
     Outdated,
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
 pub struct ModInstallInfo {
     pub mod_name: String,
     pub local_version: Option<String>,
@@ -93,6 +137,9 @@ pub fn check_install_status(
     }
 }
 
+/// Writes `data` into the folder the routing table gives `content_type`,
+/// replacing whichever version of the mod was there before.
+#[allow(clippy::too_many_arguments)]
 pub fn install_mod(
     folder: &Path,
     file_name: &str,
@@ -102,45 +149,326 @@ pub fn install_mod(
     file_id: String,
     version_name: String,
     api_provider: ApiProvider,
+    content_type: ContentType,
+    settings: &mut AppSettings,
+) -> Result<(), String> {
+    let entry = InstalledModEntry {
+        mod_id, mod_name, file_id, version_name, api_provider, content_type,
+        dependencies: vec![],
+        is_dependency: false,
+        incompatible: vec![],
+        disabled: false,
+    };
+    install_with(folder, file_name, entry, settings, |dest| fs::write(dest, data))
+}
+
+/// Moves a file streamed by `download_version_to_file_unified` into the
+/// folder the routing table gives `content_type`, replacing whichever version
+/// of the mod was there before.
+#[allow(clippy::too_many_arguments)]
+pub fn install_downloaded_mod(
+    folder: &Path,
+    download: DownloadedFile,
+    mod_id: String,
+    mod_name: String,
+    file_id: String,
+    version_name: String,
+    api_provider: ApiProvider,
+    content_type: ContentType,
     settings: &mut AppSettings,
 ) -> Result<(), String> {
-    let mods_dir = get_mods_dir(folder);
-    if !mods_dir.exists() {
-        fs::create_dir_all(&mods_dir).map_err(|e| e.to_string())?;
+    let entry = InstalledModEntry {
+        mod_id, mod_name, file_id, version_name, api_provider, content_type,
+        dependencies: vec![],
+        is_dependency: false,
+        incompatible: vec![],
+        disabled: false,
+    };
+    install_downloaded_entry(folder, download, entry, settings)
+}
+
+/// `install_downloaded_mod` with the manifest entry built by the caller,
+/// for installs that also record dependencies.
+pub fn install_downloaded_entry(
+    folder: &Path,
+    download: DownloadedFile,
+    entry: InstalledModEntry,
+    settings: &mut AppSettings,
+) -> Result<(), String> {
+    let file_name = download.file_name.clone();
+
+    // The downloads folder sits beside Mods, so staging is normally a rename.
+    install_with(folder, &file_name, entry, settings, |staged| move_file(&download.path, staged))
+}
+
+/// Moves `from` to `to`, copying when they are on different filesystems.
+pub(crate) fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::rename(from, to).or_else(|_| {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    })
+}
+
+/// Installs a copy of a file from elsewhere on disk, leaving the original in place.
+pub fn install_local_file(
+    folder: &Path,
+    source: &Path,
+    file_name: &str,
+    entry: InstalledModEntry,
+    settings: &mut AppSettings,
+) -> Result<(), String> {
+    install_with(folder, file_name, entry, settings, |staged| fs::copy(source, staged).map(|_| ()))
+}
+
+/// Installs a file as one step: the new file is staged in the downloads
+/// folder, the old version is set aside, and the new one is swapped in before
+/// the manifest is saved. If any step fails, the old file and manifest entry
+/// are put back; otherwise a different version that was replaced goes to the
+/// archive.
+fn install_with(
+    folder: &Path,
+    file_name: &str,
+    mut entry: InstalledModEntry,
+    settings: &mut AppSettings,
+    write: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> Result<(), String> {
+    let staging_dir = get_downloads_dir(folder);
+    fs::create_dir_all(&staging_dir).map_err(|e| e.to_string())?;
+
+    let staged = staging_dir.join(format!("{}.staged", file_name));
+    if let Err(e) = write(&staged) {
+        let _ = fs::remove_file(&staged);
+        return Err(format!("Failed to stage {}: {}", file_name, e));
+    }
+
+    let new_file_id = entry.file_id.clone();
+    let previous = settings.installed_mods.iter()
+        .find(|(_, v)| v.mod_id == entry.mod_id)
+        .map(|(name, old)| (name.clone(), old.clone()));
+    if let Some((_, old_entry)) = &previous {
+        // A disabled mod stays disabled. Whether it is a dependency is up to
+        // the caller, since an explicit install makes it the user's own.
+        entry.disabled |= old_entry.disabled;
     }
 
-    if let Some((old_filename, _)) = settings.installed_mods.iter().find(|(_, v)| v.mod_id == mod_id) {
-        let old_path = mods_dir.join(old_filename);
-        if old_path.exists() {
-            let _ = fs::remove_file(old_path);
+    // The old file is set aside rather than deleted until the swap is done.
+    let backup = match &previous {
+        Some((old_name, old_entry)) if installed_path(folder, old_name, Some(old_entry), &settings.routes).exists() => {
+            let old_path = installed_path(folder, old_name, Some(old_entry), &settings.routes);
+            let backup = staging_dir.join(format!("{}.backup", old_name));
+            if let Err(e) = move_file(&old_path, &backup) {
+                let _ = fs::remove_file(&staged);
+                return Err(format!("Failed to set aside {}: {}", old_name, e));
+            }
+            Some((old_path, backup))
+        }
+        _ => None,
+    };
+    let restore_backup = |backup: &Option<(PathBuf, PathBuf)>| {
+        if let Some((old_path, path)) = backup {
+            if let Err(e) = move_file(path, old_path) {
+                eprintln!("Failed to restore {}: {}", old_path.display(), e);
+            }
         }
+    };
 
-        let old_key = old_filename.clone();
-        settings.remove_installed_mod(&old_key);
+    let dest = installed_path(folder, file_name, Some(&entry), &settings.routes);
+    if let Err(e) = dest.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| move_file(&staged, &dest)) {
+        let _ = fs::remove_file(&staged);
+        restore_backup(&backup);
+        return Err(format!("Failed to install {}: {}", file_name, e));
     }
 
-    fs::write(mods_dir.join(file_name), data).map_err(|e| e.to_string())?;
+    if let Some((old_name, _)) = &previous {
+        settings.installed_mods.remove(old_name);
+    }
+    settings.installed_mods.insert(file_name.to_string(), entry);
 
-    let entry = InstalledModEntry {
-        mod_id,
-        mod_name,
-        file_id,
-        version_name,
-        api_provider,
-    };
-    settings.add_installed_mod(file_name.to_string(), entry);
+    if let Err(e) = settings.save() {
+        settings.installed_mods.remove(file_name);
+        if let Some((old_name, old_entry)) = previous {
+            settings.installed_mods.insert(old_name, old_entry);
+        }
+        let _ = fs::remove_file(&dest);
+        restore_backup(&backup);
+        return Err(format!("Failed to save the manifest: {}", e));
+    }
 
+    if let (Some((_, path)), Some((old_name, old_entry))) = (backup, &previous) {
+        if old_entry.file_id != new_file_id {
+            if let Err(e) = archive_file(settings, old_name, old_entry, &path) {
+                eprintln!("Failed to archive {}: {}", old_name, e);
+            }
+        }
+        // Already gone if it was archived.
+        let _ = fs::remove_file(path);
+    }
     Ok(())
 }
 
-pub fn remove_mod(folder: &Path, file_name: &str, settings: &mut AppSettings) -> Result<(), String> {
-    let mods_dir = get_mods_dir(folder);
+/// Puts an archived version back in place of the installed one, which is
+/// archived in turn. The archive copy is only removed once the install
+/// succeeded.
+pub fn restore_archived(folder: &Path, archived: &ArchivedVersion, settings: &mut AppSettings) -> Result<(), String> {
+    let mut entry = archived.entry.clone();
+    // Like an update, a rollback doesn't change why the mod is installed.
+    if let Some(current) = settings.installed_mods.values().find(|e| e.mod_id == entry.mod_id) {
+        entry.is_dependency = current.is_dependency;
+    }
+    install_local_file(folder, &archived.file_path(), &archived.file_name, entry, settings)?;
+    let _ = fs::remove_dir_all(&archived.path);
+    Ok(())
+}
+
+/// Restores the most recently replaced version of a mod.
+pub fn roll_back(folder: &Path, api_provider: &ApiProvider, mod_id: &str, settings: &mut AppSettings) -> Result<ArchivedVersion, String> {
+    let archived = archived_versions(settings, api_provider, mod_id)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No earlier version of mod {} is archived", mod_id))?;
+    restore_archived(folder, &archived, settings)?;
+    Ok(archived)
+}
 
-    let path = mods_dir.join(file_name);
+pub fn remove_mod(folder: &Path, file_name: &str, settings: &mut AppSettings) -> Result<(), String> {
+    let path = installed_path(folder, file_name, settings.get_installed_mod(file_name), &settings.routes);
     if path.exists() {
         fs::remove_file(path).map_err(|e| e.to_string())?;
     }
     settings.remove_installed_mod(file_name);
 
     Ok(())
-}
\ No newline at end of file
+}
+/// File names of the installed mods that need `file_name`, directly or
+/// through another mod that does, nearest first.
+pub fn find_dependents(settings: &AppSettings, file_name: &str) -> Vec<String> {
+    let mut dependents: Vec<String> = Vec::new();
+    let mut pending = vec![file_name.to_string()];
+
+    while let Some(current) = pending.pop() {
+        let Some(required) = settings.get_installed_mod(&current) else { continue };
+        let mut found: Vec<String> = settings.installed_mods.iter()
+            .filter(|(name, entry)| {
+                name.as_str() != file_name && !dependents.contains(name) && entry.requires(required)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        found.sort();
+        pending.extend(found.iter().cloned());
+        dependents.extend(found);
+    }
+
+    dependents
+}
+
+/// File names of mods that were only installed as dependencies and that no
+/// mod the user installed still needs, directly or indirectly.
+pub fn find_orphans(settings: &AppSettings) -> Vec<String> {
+    let mut needed: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = settings.installed_mods.iter()
+        .filter(|(_, entry)| !entry.is_dependency)
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    while let Some(current) = pending.pop() {
+        if !needed.insert(current) {
+            continue;
+        }
+        let entry = &settings.installed_mods[current];
+        pending.extend(settings.installed_mods.iter()
+            .filter(|(_, other)| entry.requires(other))
+            .map(|(name, _)| name.as_str()));
+    }
+
+    let mut orphans: Vec<String> = settings.installed_mods.keys()
+        .filter(|name| !needed.contains(name.as_str()))
+        .cloned()
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+/// Removes `file_name` and every mod that depends on it, dependents first.
+/// Returns the dependents that were removed.
+pub fn remove_mod_with_dependents(folder: &Path, file_name: &str, settings: &mut AppSettings) -> Result<Vec<String>, String> {
+    let dependents = find_dependents(settings, file_name);
+    for dependent in dependents.iter().rev() {
+        remove_mod(folder, dependent, settings)?;
+    }
+    remove_mod(folder, file_name, settings)?;
+    Ok(dependents)
+}
+
+/// Moves an installed mod out of the Mods folder into the holding folder, or
+/// back, and records the change in the manifest.
+pub fn set_mod_enabled(folder: &Path, file_name: &str, enabled: bool, settings: &mut AppSettings) -> Result<(), String> {
+    let entry = settings.get_installed_mod(file_name)
+        .cloned()
+        .ok_or_else(|| format!("{} is not in the manifest", file_name))?;
+    if entry.disabled != enabled {
+        return Ok(());
+    }
+
+    let from = installed_path(folder, file_name, Some(&entry), &settings.routes);
+    let mut toggled = entry.clone();
+    toggled.disabled = !enabled;
+    let to = installed_path(folder, file_name, Some(&toggled), &settings.routes);
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    move_file(&from, &to).map_err(|e| format!("Failed to move {}: {}", file_name, e))?;
+
+    settings.installed_mods.insert(file_name.to_string(), toggled);
+    if let Err(e) = settings.save() {
+        settings.installed_mods.insert(file_name.to_string(), entry);
+        let _ = move_file(&to, &from);
+        return Err(format!("Failed to save the manifest: {}", e));
+    }
+    Ok(())
+}
+
+/// Points `content_type` at `new_folder` (relative to the game folder) and
+/// moves the enabled files of that type already installed there. If a file
+/// can't be moved, the ones already moved go back and the route is unchanged.
+pub fn set_route(folder: &Path, content_type: ContentType, new_folder: PathBuf, settings: &mut AppSettings) -> Result<(), String> {
+    let old_dir = settings.routes.dir(folder, content_type);
+    let new_dir = folder.join(&new_folder);
+    let files: Vec<String> = settings.installed_mods.iter()
+        .filter(|(file_name, e)| e.content_type == content_type && !e.disabled && old_dir.join(file_name).exists())
+        .filter(|_| old_dir != new_dir)
+        .map(|(file_name, _)| file_name.clone())
+        .collect();
+
+    let mut moved = Vec::new();
+    let mut result = Ok(());
+    if !files.is_empty() {
+        result = fs::create_dir_all(&new_dir).map_err(|e| format!("Failed to create {}: {}", new_dir.display(), e));
+    }
+    for file_name in &files {
+        if result.is_err() {
+            break;
+        }
+        match move_file(&old_dir.join(file_name), &new_dir.join(file_name)) {
+            Ok(()) => moved.push(file_name),
+            Err(e) => result = Err(format!("Failed to move {}: {}", file_name, e)),
+        }
+    }
+
+    let previous = settings.routes.folders.insert(content_type, new_folder);
+    if result.is_ok() {
+        result = settings.save().map_err(|e| format!("Failed to save the settings: {}", e));
+    }
+    if result.is_err() {
+        match previous {
+            Some(previous) => settings.routes.folders.insert(content_type, previous),
+            None => settings.routes.folders.remove(&content_type),
+        };
+        for file_name in moved {
+            if let Err(e) = move_file(&new_dir.join(file_name), &old_dir.join(file_name)) {
+                eprintln!("Failed to move back {}: {}", file_name, e);
+            }
+        }
+    }
+    result
+}
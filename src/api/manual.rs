@@ -0,0 +1,112 @@
+//! Mods whose authors only allow downloads from the provider's website. The
+//! user fetches the file in a browser and the manager picks it up from their
+//! downloads folder.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use crate::api::local_mods::install_local_file;
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
+use crate::api::ui_mod::{UiMod, UiModVersion};
+
+/// How often `wait_for_download` looks at the folder.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Page where the user can download `version` by hand.
+pub fn download_page(ui_mod: &UiMod, version: &UiModVersion) -> String {
+    match ui_mod.provider {
+        ApiProvider::CurseForge if !version.file_id.is_empty() => {
+            format!("{}/files/{}", ui_mod.website_url.trim_end_matches('/'), version.file_id)
+        }
+        _ => ui_mod.website_url.clone(),
+    }
+}
+
+/// Opens `url` in the default browser.
+pub fn open_url(url: &str) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command.arg(url).spawn().map(|_| ())
+}
+
+fn is_download_of(path: &Path, version: &UiModVersion) -> bool {
+    let Ok(meta) = fs::metadata(path) else { return false };
+    if !meta.is_file() || version.file_size.is_some_and(|size| size != meta.len()) {
+        return false;
+    }
+    fs::read(path).is_ok_and(|data| version.verify(&data).is_ok())
+}
+
+/// Looks in `dir` for a finished download of `version`. The file is accepted
+/// under its own name, or under a name the browser made unique
+/// (`Mod (1).jar`) when the provider published checksums to match it by.
+/// Files still being written fail the size or checksum check and are skipped.
+pub fn find_download(dir: &Path, version: &UiModVersion) -> Option<PathBuf> {
+    let exact = dir.join(&version.file_name);
+    if is_download_of(&exact, version) {
+        return Some(exact);
+    }
+    if version.checksums.is_empty() {
+        return None;
+    }
+
+    let extension = Path::new(&version.file_name).extension();
+    fs::read_dir(dir).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| *path != exact && path.extension() == extension)
+        .find(|path| is_download_of(path, version))
+}
+
+/// Polls `dir` until `find_download` succeeds, or `timeout` passes.
+pub async fn wait_for_download(dir: &Path, version: &UiModVersion, timeout: Option<Duration>) -> Option<PathBuf> {
+    let started = Instant::now();
+    loop {
+        if let Some(path) = find_download(dir, version) {
+            return Some(path);
+        }
+        if timeout.is_some_and(|t| started.elapsed() >= t) {
+            return None;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Installs a file the user downloaded by hand and records it in the manifest
+/// as if it had come through the install queue. The copy in the downloads
+/// folder is left alone.
+pub fn import_download(
+    hytale_folder: &Path,
+    path: &Path,
+    ui_mod: &UiMod,
+    version: &UiModVersion,
+    settings: &mut AppSettings,
+) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    version.verify(&data)?;
+
+    let entry = InstalledModEntry {
+        mod_id: ui_mod.id.clone(),
+        mod_name: ui_mod.name.clone(),
+        file_id: version.file_id.clone(),
+        version_name: version.display_name.clone(),
+        api_provider: ui_mod.provider.clone(),
+        dependencies: version.required_mod_ids(),
+        is_dependency: false,
+        incompatible: version.incompatible_mod_ids(),
+        disabled: false,
+        content_type: settings.routes.classify_mod(ui_mod),
+    };
+    install_local_file(hytale_folder, path, &version.file_name, entry, settings)
+}
@@ -1,5 +1,16 @@
+pub mod archive;
+pub mod checksum;
 pub mod curse_forge_api;
+pub mod dependencies;
+pub mod download;
+pub mod federated;
+pub mod http;
+pub mod local_file;
 pub mod mod_tale_api;
+pub mod provider;
+pub mod queue;
+pub mod routing;
 pub mod settings;
 pub mod local_mods;
+pub mod manual;
 pub mod ui_mod;
\ No newline at end of file
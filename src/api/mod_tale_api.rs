@@ -1,25 +1,26 @@
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, USER_AGENT};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, RANGE, USER_AGENT};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, OnceLock, RwLock};
-use reqwest::Client;
+use std::sync::{Arc, RwLock};
+use reqwest::{Client, Response};
+use crate::api::http::{RequestLayer, RetryPolicy, TokenBucket};
+use crate::api::provider::{BaseUrls, ModProvider};
+use crate::api::settings::ApiProvider;
+use crate::api::ui_mod::{supports_game_version, SearchFilter, UiMod, UiModVersion};
 
 const MODTALE_API: &str = "https://api.modtale.net/api/v1";
+/// Requests allowed in a burst, and per second after it.
+const REQUESTS_BURST: u32 = 20;
+const REQUESTS_PER_SECOND: f64 = 10.0;
 const MODTALE_CDN: &str = "https://cdn.modtale.net";
 
-static CLIENT: OnceLock<RwLock<Arc<Client>>> = OnceLock::new();
-
-fn client_store() -> &'static RwLock<Arc<Client>> {
-    CLIENT.get_or_init(|| RwLock::new(Arc::new(build_client(None))))
-}
-
 fn build_client(api_key: Option<&str>) -> Client {
     let mut headers = HeaderMap::new();
     headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
     headers.insert(USER_AGENT, HeaderValue::from_static("HytaleModManager/1.0"));
 
     if let Some(key) = api_key {
-        let auth_val = format!("{}", key);
-        if let Ok(val) = HeaderValue::from_str(&auth_val) {
+        if let Ok(val) = HeaderValue::from_str(key) {
             headers.insert("X-MODTALE-KEY", val);
         }
     }
@@ -30,18 +31,6 @@ fn build_client(api_key: Option<&str>) -> Client {
         .expect("Failed to build HTTP client")
 }
 
-fn client() -> Arc<Client> {
-    client_store().read().unwrap().clone()
-}
-
-pub fn set_global_api_key(key: &str) {
-    let new_client = Arc::new(build_client(Some(key)));
-    if let Ok(mut lock) = client_store().write() {
-        *lock = new_client;
-        println!("Global API key applied to all future requests on ModTale.");
-    }
-}
-
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PageResponse<T> {
@@ -96,6 +85,25 @@ pub struct ModTaleFile {
     pub created_at: String,
     pub download_count: u64,
     pub channel: Option<String>,
+    #[serde(default, alias = "fileSize")]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub sha1: Option<String>,
+    #[serde(default)]
+    pub md5: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<ModTaleDependency>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModTaleDependency {
+    #[serde(alias = "projectId")]
+    pub mod_id: String,
+    #[serde(default)]
+    pub optional: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -106,90 +114,236 @@ pub struct Meta {
     pub per_page: u32,
 }
 
-pub async fn search_mods(query: String, sort: &str, offset: u32) -> Result<(Vec<ModTaleMod>, Option<Meta>), String> {
-    let url = format!("{}/projects", MODTALE_API);
+pub struct ModTaleProvider {
+    client: RwLock<Arc<Client>>,
+    requests: RequestLayer,
+    base_urls: RwLock<BaseUrls>,
+    game_version: RwLock<Option<String>>,
+}
+
+impl Default for ModTaleProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-    let limit: u32 = 20;
-    let page_index = offset / limit;
+impl ModTaleProvider {
+    pub fn new() -> Self {
+        Self {
+            client: RwLock::new(Arc::new(build_client(None))),
+            requests: RequestLayer::new(
+                "ModTale",
+                TokenBucket::new(REQUESTS_BURST, REQUESTS_PER_SECOND),
+                RetryPolicy::default(),
+            ),
+            base_urls: RwLock::new(BaseUrls::default()),
+            game_version: RwLock::new(None),
+        }
+    }
 
-    let params = [
-        ("q", query.as_str()),
-        ("sort", sort),
-        ("page", &page_index.to_string()),
-        ("size", &limit.to_string()),
-    ];
+    fn client(&self) -> Arc<Client> {
+        self.client.read().unwrap().clone()
+    }
 
-    println!("[ModTale DEBUG] Searching Page: {} (Limit: {})", page_index, limit);
+    fn game_version(&self) -> Option<String> {
+        self.game_version.read().unwrap().clone()
+    }
 
-    let resp = client()
-        .get(&url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("Network Request Failed: {}", e))?;
+    fn api_url(&self) -> String {
+        BaseUrls::resolve(&self.base_urls.read().unwrap().api, MODTALE_API)
+    }
 
-    if !resp.status().is_success() {
-        return Err(format!("ModTale API Error: {}", resp.status()));
+    fn cdn_url(&self) -> String {
+        BaseUrls::resolve(&self.base_urls.read().unwrap().cdn, MODTALE_CDN)
     }
 
-    let json: PageResponse<ModTaleMod> = resp.json()
-        .await
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    pub async fn search_mods(&self, query: String, sort: &str, offset: u32) -> Result<(Vec<ModTaleMod>, Option<Meta>), String> {
+        let url = format!("{}/projects", self.api_url());
 
-    let meta = Meta {
-        current_page: page_index,
-        last_page: json.total_pages,
-        total: json.total_elements as u32,
-        per_page: limit,
-    };
+        let limit: u32 = 20;
+        let page_index = offset / limit;
 
-    Ok((json.content, Some(meta)))
-}
+        let params = [
+            ("q", query.as_str()),
+            ("sort", sort),
+            ("page", &page_index.to_string()),
+            ("size", &limit.to_string()),
+        ];
 
-pub async fn get_mod(mod_id: &str) -> Result<ModTaleMod, String> {
-    let url = format!("{}/projects/{}", MODTALE_API, mod_id);
+        eprintln!("[ModTale DEBUG] Searching Page: {} (Limit: {})", page_index, limit);
 
-    let resp = client()
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Network Request Failed: {}", e))?;
+        let request = self.client()
+            .get(&url)
+            .query(&params);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Network Request Failed: {}", e))?;
 
-    if !resp.status().is_success() {
-        return Err(format!("API Error {}: {}", resp.status(), resp.text().await.unwrap_or_default()));
+        if !resp.status().is_success() {
+            return Err(format!("ModTale API Error: {}", resp.status()));
+        }
+
+        let json: PageResponse<ModTaleMod> = resp.json()
+            .await
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+        let meta = Meta {
+            current_page: page_index,
+            last_page: json.total_pages,
+            total: json.total_elements as u32,
+            per_page: limit,
+        };
+
+        Ok((json.content, Some(meta)))
     }
 
-    let mod_data: ModTaleMod = resp.json()
-        .await
-        .map_err(|e| format!("Serde Parsing Error: {}", e))?;
+    pub async fn get_mod(&self, mod_id: &str) -> Result<ModTaleMod, String> {
+        let url = format!("{}/projects/{}", self.api_url(), mod_id);
 
-    Ok(mod_data)
-}
+        let request = self.client().get(&url);
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Network Request Failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("API Error {}: {}", resp.status(), resp.text().await.unwrap_or_default()));
+        }
+
+        let mod_data: ModTaleMod = resp.json()
+            .await
+            .map_err(|e| format!("Serde Parsing Error: {}", e))?;
 
-pub async fn get_mod_files(mod_id: &str) -> Result<Vec<ModTaleFile>, String> {
-    let mod_data = get_mod(mod_id).await?;
-    Ok(mod_data.versions.unwrap_or_default())
+        Ok(mod_data)
+    }
+
+    pub async fn get_mod_files(&self, mod_id: &str) -> Result<Vec<ModTaleFile>, String> {
+        let mod_data = self.get_mod(mod_id).await?;
+        Ok(mod_data.versions.unwrap_or_default())
+    }
+
+    /// Sends the download request and hands back the response unread, so
+    /// the body can be streamed. Relative URLs resolve against the CDN, and a
+    /// non-zero `offset` asks for the rest of the file only.
+    pub async fn open_download(&self, url: &str, offset: u64) -> Result<Response, String> {
+        let full_url = if url.starts_with("http") {
+            url.to_string()
+        } else {
+            format!("{}/{}", self.cdn_url(), url.trim_start_matches('/'))
+        };
+
+        eprintln!("[ModTale] Downloading from: {}", full_url);
+
+        let mut request = self.client().get(&full_url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+
+        let resp = self.requests.send(request)
+            .await
+            .map_err(|e| format!("Request Send Error: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("Download failed: {}", resp.status()));
+        }
+
+        Ok(resp)
+    }
+
+    pub async fn download_url(&self, url: &str) -> Result<Vec<u8>, String> {
+        let bytes = self.open_download(url, 0).await?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read bytes: {}", e))?;
+        Ok(bytes.to_vec())
+    }
 }
 
-pub async fn download_url(url: &str) -> Result<Vec<u8>, String> {
-    let full_url = if url.starts_with("http") {
-        url.to_string()
-    } else {
-        format!("{}/{}", MODTALE_CDN, url)
-    };
+#[async_trait]
+impl ModProvider for ModTaleProvider {
+    fn id(&self) -> ApiProvider {
+        ApiProvider::Modtale
+    }
 
-    println!("[ModTale] Downloading from: {}", full_url);
+    fn set_api_key(&self, key: &str) {
+        let new_client = Arc::new(build_client(Some(key)));
+        if let Ok(mut lock) = self.client.write() {
+            *lock = new_client;
+            eprintln!("API key applied to all future requests on ModTale.");
+        }
+    }
 
-    let resp = client()
-        .get(&full_url)
-        .send()
-        .await
-        .map_err(|e| format!("Request Send Error: {}", e))?;
+    fn set_base_urls(&self, urls: &BaseUrls) {
+        if let Ok(mut lock) = self.base_urls.write() {
+            *lock = urls.clone();
+        }
+    }
+
+    fn set_game_version(&self, version: Option<&str>) {
+        if let Ok(mut lock) = self.game_version.write() {
+            *lock = version.map(str::to_string);
+        }
+    }
+
+    fn requires_api_key(&self) -> bool {
+        false
+    }
 
-    if !resp.status().is_success() {
-        return Err(format!("Download failed: {}", resp.status()));
+    async fn search(
+        &self,
+        query: String,
+        sort: u32,
+        offset: u32,
+        _filter: &SearchFilter,
+    ) -> Result<(Vec<UiMod>, u32), String> {
+        let sort = match sort {
+            1 => "relevance",
+            2 => "downloads",
+            3 => "updated",
+            _ => "downloads",
+        };
+
+        let (api_mods, meta) = self.search_mods(query, sort, offset).await?;
+        let game_version = self.game_version();
+
+        // The projects endpoint has no version filter, so projects whose
+        // listed files all target another client are dropped here.
+        let ui_mods = api_mods.iter()
+            .filter(|m| match (&game_version, &m.versions) {
+                (Some(target), Some(versions)) if !versions.is_empty() => {
+                    versions.iter().any(|v| supports_game_version(&v.supported_versions, target))
+                }
+                _ => true,
+            })
+            .map(|m| UiMod::from_modtale_api(m, game_version.as_deref()))
+            .collect();
+
+        let total_pages = if let Some(m) = meta {
+            m.last_page
+        } else {
+            0
+        };
+
+        Ok((ui_mods, total_pages))
     }
 
-    let bytes = resp.bytes().await.map_err(|e| format!("Failed to read bytes: {}", e))?;
-    Ok(bytes.to_vec())
-}
\ No newline at end of file
+    async fn get_versions(&self, mod_id: &str) -> Result<Vec<UiModVersion>, String> {
+        match self.get_mod_files(mod_id).await {
+            Ok(files) => Ok(files.iter()
+                .map(UiModVersion::from_modtale_file)
+                .collect()),
+            Err(e) => {
+                eprintln!("ModTale Versions fetch FAILED: {}", e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn get_details(&self, mod_id: &str) -> Result<UiMod, String> {
+        let game_version = self.game_version();
+        self.get_mod(mod_id).await.map(|m| UiMod::from_modtale_api(&m, game_version.as_deref()))
+    }
+
+    async fn open_download(&self, url: &str, offset: u64) -> Result<Response, String> {
+        ModTaleProvider::open_download(self, url, offset).await
+    }
+}
@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use crate::api::curse_forge_api::CurseForgeProvider;
+use crate::api::mod_tale_api::ModTaleProvider;
+use crate::api::settings::ApiProvider;
+use crate::api::ui_mod::{SearchFilter, UiCategory, UiMod, UiModVersion, VersionPage};
+
+/// Base URL overrides for a provider. `None` keeps the built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BaseUrls {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn: Option<String>,
+}
+
+impl BaseUrls {
+    /// Resolves one URL against its default, dropping any trailing slash.
+    pub fn resolve(value: &Option<String>, default: &str) -> String {
+        value.as_deref()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or(default)
+            .trim()
+            .trim_end_matches('/')
+            .to_string()
+    }
+}
+
+/// Upper bound on concurrent detail requests against one provider.
+pub const DETAILS_CONCURRENCY: usize = 6;
+
+/// A mod source. Each backend owns its HTTP client and converts its own
+/// response models into `UiMod` / `UiModVersion`.
+#[async_trait]
+pub trait ModProvider: Send + Sync {
+    fn id(&self) -> ApiProvider;
+
+    fn set_api_key(&self, key: &str);
+
+    fn set_base_urls(&self, urls: &BaseUrls);
+
+    /// Hytale version that searches and version picks should target,
+    /// `None` for any.
+    fn set_game_version(&self, version: Option<&str>);
+
+    /// Whether requests fail without a key, as opposed to the key being optional.
+    fn requires_api_key(&self) -> bool;
+
+    /// Whether `search` honours a `SearchFilter`. Providers without a
+    /// category tree ignore it.
+    fn supports_categories(&self) -> bool {
+        false
+    }
+
+    /// `sort` is the index of the sort dropdown on the Search page.
+    async fn search(
+        &self,
+        query: String,
+        sort: u32,
+        offset: u32,
+        filter: &SearchFilter,
+    ) -> Result<(Vec<UiMod>, u32), String>;
+
+    /// Every class and category mods can be filtered by.
+    async fn categories(&self) -> Result<Vec<UiCategory>, String> {
+        Ok(vec![])
+    }
+
+    /// The complete file history of a mod.
+    async fn get_versions(&self, mod_id: &str) -> Result<Vec<UiModVersion>, String>;
+
+    /// Files starting at `offset`, for lists that load older versions on
+    /// demand. Providers that return everything at once answer with one page.
+    async fn get_versions_page(&self, mod_id: &str, offset: u32) -> Result<VersionPage, String> {
+        let versions: Vec<_> = self.get_versions(mod_id).await?
+            .into_iter()
+            .skip(offset as usize)
+            .collect();
+        let total_count = Some(offset + versions.len() as u32);
+
+        Ok(VersionPage { versions, offset, total_count, has_more: false })
+    }
+
+    async fn get_details(&self, mod_id: &str) -> Result<UiMod, String>;
+
+    /// Details for many mods at once. Mods that cannot be fetched are left
+    /// out; the default runs a few `get_details` calls in parallel.
+    async fn get_details_batch(&self, mod_ids: &[String]) -> Result<Vec<UiMod>, String> {
+        let results: Vec<_> = stream::iter(mod_ids.to_vec())
+            .map(|id| async move {
+                let result = self.get_details(&id).await;
+                (id, result)
+            })
+            .buffer_unordered(DETAILS_CONCURRENCY)
+            .collect()
+            .await;
+
+        Ok(results.into_iter()
+            .filter_map(|(id, result)| match result {
+                Ok(ui_mod) => Some(ui_mod),
+                Err(e) => {
+                    eprintln!("{:?} fetch FAILED for {}: {}", self.id(), id, e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Starts a file download, leaving the body unread so callers can
+    /// stream it instead of buffering the whole file. With a non-zero
+    /// `offset` the server is asked to resume from that byte; it may still
+    /// answer with the whole file.
+    async fn open_download(&self, url: &str, offset: u64) -> Result<Response, String>;
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, String> {
+        let bytes = self.open_download(url, 0).await?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read bytes: {}", e))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+pub struct ProviderRegistry {
+    providers: HashMap<ApiProvider, Arc<dyn ModProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self { providers: HashMap::new() }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(CurseForgeProvider::new()));
+        registry.register(Arc::new(ModTaleProvider::new()));
+        registry
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn ModProvider>) {
+        self.providers.insert(provider.id(), provider);
+    }
+
+    pub fn get(&self, id: &ApiProvider) -> Option<Arc<dyn ModProvider>> {
+        self.providers.get(id).cloned()
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &Arc<dyn ModProvider>> {
+        self.providers.values()
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static REGISTRY: OnceLock<ProviderRegistry> = OnceLock::new();
+
+pub fn registry() -> &'static ProviderRegistry {
+    REGISTRY.get_or_init(ProviderRegistry::with_defaults)
+}
+
+pub fn provider(id: &ApiProvider) -> Result<Arc<dyn ModProvider>, String> {
+    registry().get(id).ok_or_else(|| format!("No provider registered for {:?}", id))
+}
+
+pub fn set_game_version(version: Option<&str>) {
+    for provider in registry().all() {
+        provider.set_game_version(version);
+    }
+}
+
+pub fn set_api_key(id: &ApiProvider, key: &str) {
+    if let Some(provider) = registry().get(id) {
+        provider.set_api_key(key);
+    }
+}
@@ -0,0 +1,377 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use serde::Serialize;
+use tokio::sync::{watch, Notify};
+use crate::api::download::{progress_channel, DownloadProgress, DownloadedFile};
+use crate::api::local_mods::install_downloaded_entry;
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
+use crate::api::ui_mod::{stream_version_unified, UiMod, UiModVersion};
+
+pub const DEFAULT_PARALLEL_DOWNLOADS: usize = 3;
+
+pub type JobId = u64;
+
+/// The step a job was on when it failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStage {
+    Download,
+    Verify,
+    Install,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Downloading { progress: DownloadProgress },
+    Verifying,
+    Installing,
+    Failed { stage: JobStage, error: String },
+    Cancelled,
+    Done,
+}
+
+impl JobState {
+    /// Queued or in flight.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            JobState::Queued | JobState::Downloading { .. } | JobState::Verifying | JobState::Installing
+        )
+    }
+
+    pub fn can_cancel(&self) -> bool {
+        matches!(self, JobState::Queued | JobState::Downloading { .. })
+    }
+
+    pub fn can_retry(&self) -> bool {
+        matches!(self, JobState::Failed { .. } | JobState::Cancelled)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobState::Queued => "Queued",
+            JobState::Downloading { .. } => "Downloading",
+            JobState::Verifying => "Verifying",
+            JobState::Installing => "Installing",
+            JobState::Failed { .. } => "Failed",
+            JobState::Cancelled => "Cancelled",
+            JobState::Done => "Done",
+        }
+    }
+}
+
+/// One mod version to download and install.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Job {
+    pub id: JobId,
+    pub mod_id: String,
+    pub mod_name: String,
+    pub provider: ApiProvider,
+    /// How the provider classifies the mod, for picking its install folder.
+    pub class_id: Option<String>,
+    pub categories: Vec<String>,
+    pub version: UiModVersion,
+    /// Name of the mod this one is installed for, when it is a dependency.
+    pub required_by: Option<String>,
+    /// Replaces the installed version without changing why the mod is installed.
+    pub update: bool,
+    pub state: JobState,
+}
+
+struct Entry {
+    job: Job,
+    cancel: Arc<Notify>,
+    /// Set once a caller has started running the job, so it only runs once.
+    claimed: bool,
+}
+
+struct Slots {
+    active: usize,
+    limit: usize,
+}
+
+/// Shared download/install queue. Jobs are added with [`InstallQueue::enqueue`]
+/// and driven by whoever calls [`InstallQueue::run`]; at most `limit` of them
+/// download at once, the rest wait in `Queued`.
+pub struct InstallQueue {
+    jobs: Mutex<Vec<Entry>>,
+    slots: Mutex<Slots>,
+    slot_freed: Notify,
+    next_id: AtomicU64,
+    changes: watch::Sender<u64>,
+}
+
+impl Default for InstallQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARALLEL_DOWNLOADS)
+    }
+}
+
+impl InstallQueue {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            slots: Mutex::new(Slots { active: 0, limit: limit.max(1) }),
+            slot_freed: Notify::new(),
+            next_id: AtomicU64::new(1),
+            changes: watch::channel(0).0,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.slots.lock().unwrap().limit
+    }
+
+    /// Takes effect as running jobs finish; nothing already downloading is
+    /// interrupted when the limit drops.
+    pub fn set_limit(&self, limit: usize) {
+        self.slots.lock().unwrap().limit = limit.max(1);
+        self.slot_freed.notify_waiters();
+    }
+
+    /// Ticks whenever any job is added, removed or changes state.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    pub fn enqueue(&self, ui_mod: &UiMod, version: UiModVersion) -> JobId {
+        self.push(ui_mod, version, None, false)
+    }
+
+    /// Queues a mod that `required_by` needs; it is recorded as a
+    /// dependency in the manifest once installed.
+    pub fn enqueue_dependency(&self, ui_mod: &UiMod, version: UiModVersion, required_by: &str) -> JobId {
+        self.push(ui_mod, version, Some(required_by.to_string()), false)
+    }
+
+    /// Queues a new version of an installed mod. Unlike [`Self::enqueue`], a
+    /// mod installed as a dependency stays marked as one.
+    pub fn enqueue_update(&self, ui_mod: &UiMod, version: UiModVersion) -> JobId {
+        self.push(ui_mod, version, None, true)
+    }
+
+    fn push(&self, ui_mod: &UiMod, version: UiModVersion, required_by: Option<String>, update: bool) -> JobId {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let job = Job {
+            id,
+            mod_id: ui_mod.id.clone(),
+            mod_name: ui_mod.name.clone(),
+            provider: ui_mod.provider.clone(),
+            class_id: ui_mod.class_id.clone(),
+            categories: ui_mod.categories.clone(),
+            version,
+            required_by,
+            update,
+            state: JobState::Queued,
+        };
+
+        self.jobs.lock().unwrap().push(Entry { job, cancel: Arc::new(Notify::new()), claimed: false });
+        self.notify();
+        id
+    }
+
+    pub fn jobs(&self) -> Vec<Job> {
+        self.jobs.lock().unwrap().iter().map(|e| e.job.clone()).collect()
+    }
+
+    pub fn job(&self, id: JobId) -> Option<Job> {
+        self.jobs.lock().unwrap().iter().find(|e| e.job.id == id).map(|e| e.job.clone())
+    }
+
+    /// Stops a queued or downloading job. Jobs that are verifying or
+    /// installing are left to finish, since they are already past the slow part.
+    pub fn cancel(&self, id: JobId) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(entry) = jobs.iter_mut().find(|e| e.job.id == id) else { return false };
+        if !entry.job.state.can_cancel() {
+            return false;
+        }
+
+        entry.job.state = JobState::Cancelled;
+        entry.cancel.notify_one();
+        drop(jobs);
+        self.notify();
+        true
+    }
+
+    /// Puts a failed or cancelled job back in the queue. The caller runs it
+    /// again with [`InstallQueue::run`].
+    pub fn retry(&self, id: JobId) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(entry) = jobs.iter_mut().find(|e| e.job.id == id) else { return false };
+        if !entry.job.state.can_retry() {
+            return false;
+        }
+
+        entry.job.state = JobState::Queued;
+        entry.cancel = Arc::new(Notify::new());
+        entry.claimed = false;
+        drop(jobs);
+        self.notify();
+        true
+    }
+
+    /// Drops every job that is no longer active.
+    pub fn clear_finished(&self) {
+        self.jobs.lock().unwrap().retain(|e| e.job.state.is_active());
+        self.notify();
+    }
+
+    /// Downloads, verifies and installs a queued job, waiting for a free slot
+    /// first. `install` moves the verified file into place. Returns the state
+    /// the job finished in.
+    pub async fn run<F>(&self, id: JobId, hytale_folder: &Path, install: F) -> JobState
+    where
+        F: FnOnce(&Job, DownloadedFile) -> Result<(), String>,
+    {
+        // Already running elsewhere, finished, or cancelled before it started.
+        let Some((job, cancel)) = self.take_queued(id) else {
+            return self.job(id).map(|j| j.state).unwrap_or(JobState::Cancelled);
+        };
+
+        let _slot = tokio::select! {
+            slot = self.acquire_slot() => slot,
+            _ = cancel.notified() => return JobState::Cancelled,
+        };
+        if !self.set_state(id, JobState::Downloading { progress: DownloadProgress::default() }) {
+            return JobState::Cancelled;
+        }
+
+        let (tx, mut rx) = progress_channel();
+        let download = stream_version_unified(&job.provider, &job.version, hytale_folder, Some(&tx));
+        tokio::pin!(download);
+
+        let streamed = loop {
+            tokio::select! {
+                result = &mut download => break result,
+                _ = cancel.notified() => return JobState::Cancelled,
+                Ok(()) = rx.changed() => {
+                    let progress = *rx.borrow_and_update();
+                    self.set_state(id, JobState::Downloading { progress });
+                }
+            }
+        };
+
+        let streamed = match streamed {
+            Ok(streamed) => streamed,
+            Err(error) => return self.fail(id, JobStage::Download, error),
+        };
+
+        if !self.set_state(id, JobState::Verifying) {
+            return JobState::Cancelled;
+        }
+        let file = match streamed.verify(&job.version) {
+            Ok(file) => file,
+            Err(error) => return self.fail(id, JobStage::Verify, error),
+        };
+
+        if !self.set_state(id, JobState::Installing) {
+            return JobState::Cancelled;
+        }
+        match install(&job, file) {
+            Ok(()) => {
+                self.set_state(id, JobState::Done);
+                JobState::Done
+            }
+            Err(error) => self.fail(id, JobStage::Install, error),
+        }
+    }
+
+    fn take_queued(&self, id: JobId) -> Option<(Job, Arc<Notify>)> {
+        self.jobs.lock().unwrap()
+            .iter_mut()
+            .find(|e| e.job.id == id && e.job.state == JobState::Queued && !e.claimed)
+            .map(|e| {
+                e.claimed = true;
+                (e.job.clone(), e.cancel.clone())
+            })
+    }
+
+    async fn acquire_slot(&self) -> Slot<'_> {
+        loop {
+            let freed = self.slot_freed.notified();
+            tokio::pin!(freed);
+            freed.as_mut().enable();
+
+            {
+                let mut slots = self.slots.lock().unwrap();
+                if slots.active < slots.limit {
+                    slots.active += 1;
+                    return Slot { queue: self };
+                }
+            }
+            freed.await;
+        }
+    }
+
+    /// Moves a job on, unless it was cancelled in the meantime.
+    fn set_state(&self, id: JobId, state: JobState) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(entry) = jobs.iter_mut().find(|e| e.job.id == id) else { return false };
+        if entry.job.state == JobState::Cancelled {
+            return false;
+        }
+
+        entry.job.state = state;
+        drop(jobs);
+        self.notify();
+        true
+    }
+
+    fn fail(&self, id: JobId, stage: JobStage, error: String) -> JobState {
+        eprintln!("Job {} failed during {:?}: {}", id, stage, error);
+        let state = JobState::Failed { stage, error };
+        self.set_state(id, state.clone());
+        state
+    }
+
+    fn notify(&self) {
+        self.changes.send_modify(|n| *n = n.wrapping_add(1));
+    }
+}
+
+struct Slot<'a> {
+    queue: &'a InstallQueue,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        self.queue.slots.lock().unwrap().active -= 1;
+        self.queue.slot_freed.notify_waiters();
+    }
+}
+
+/// Installs a job's verified file, recording it and its relations to other
+/// mods in the manifest.
+pub fn install_job(hytale_folder: &Path, job: &Job, file: DownloadedFile, settings: &mut AppSettings) -> Result<(), String> {
+    // An explicit install makes the mod the user's own; updates and
+    // dependency installs keep whatever it was before.
+    let previous = settings.installed_mods.values().find(|e| e.mod_id == job.mod_id);
+    let is_dependency = match previous {
+        Some(old) if job.update || job.required_by.is_some() => old.is_dependency,
+        _ => job.required_by.is_some(),
+    };
+    let entry = InstalledModEntry {
+        mod_id: job.mod_id.clone(),
+        mod_name: job.mod_name.clone(),
+        file_id: job.version.file_id.clone(),
+        version_name: job.version.display_name.clone(),
+        api_provider: job.provider.clone(),
+        dependencies: job.version.required_mod_ids(),
+        is_dependency,
+        incompatible: job.version.incompatible_mod_ids(),
+        disabled: false,
+        content_type: settings.routes.classify(&job.provider, job.class_id.as_deref(), &job.categories),
+    };
+    install_downloaded_entry(hytale_folder, file, entry, settings)
+}
+
+static QUEUE: OnceLock<InstallQueue> = OnceLock::new();
+
+/// The queue shared by the GUI and the CLI.
+pub fn install_queue() -> &'static InstallQueue {
+    QUEUE.get_or_init(InstallQueue::default)
+}
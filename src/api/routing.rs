@@ -0,0 +1,132 @@
+//! Which folder of the game each kind of resource is installed into. Mods,
+//! asset packs, prefabs and worlds each have their own folder, picked from
+//! how the provider classifies the project.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::api::settings::ApiProvider;
+use crate::api::ui_mod::UiMod;
+
+/// Serialized as its CLI id, so JSON output can be passed back to `--type`.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContentType {
+    #[default]
+    Mod,
+    AssetPack,
+    Prefab,
+    World,
+}
+
+impl ContentType {
+    pub const ALL: [ContentType; 4] = [ContentType::Mod, ContentType::AssetPack, ContentType::Prefab, ContentType::World];
+
+    /// Stable lowercase id used in CLI arguments.
+    pub fn id(&self) -> &'static str {
+        match self {
+            ContentType::Mod => "mod",
+            ContentType::AssetPack => "asset-pack",
+            ContentType::Prefab => "prefab",
+            ContentType::World => "world",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.id() == id.trim().to_lowercase())
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ContentType::Mod => "Mods",
+            ContentType::AssetPack => "Asset Packs",
+            ContentType::Prefab => "Prefabs",
+            ContentType::World => "Worlds",
+        }
+    }
+
+    fn default_folder(&self) -> PathBuf {
+        let name = match self {
+            ContentType::Mod => "Mods",
+            ContentType::AssetPack => "Packs",
+            ContentType::Prefab => "Prefabs",
+            ContentType::World => "Saves",
+        };
+        Path::new("UserData").join(name)
+    }
+}
+
+/// Maps provider classifications to content types, and content types to
+/// folders relative to the game folder. Anything unmapped is treated as a mod.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RoutingTable {
+    pub folders: HashMap<ContentType, PathBuf>,
+    /// CurseForge class ids. Only the Mods (9137) and Worlds (9200) classes
+    /// are known; asset packs and prefabs have no known class id, so until
+    /// one is added here (`hmm-cli categories` lists the classes) they
+    /// install as mods.
+    pub curseforge_classes: HashMap<String, ContentType>,
+    /// Modtale category names, matched ignoring case.
+    pub modtale_categories: HashMap<String, ContentType>,
+}
+
+impl Default for RoutingTable {
+    fn default() -> Self {
+        Self {
+            folders: ContentType::ALL.into_iter().map(|t| (t, t.default_folder())).collect(),
+            curseforge_classes: HashMap::from([
+                ("9137".to_string(), ContentType::Mod),
+                ("9200".to_string(), ContentType::World),
+            ]),
+            modtale_categories: HashMap::from([
+                ("Asset Packs".to_string(), ContentType::AssetPack),
+                ("Prefabs".to_string(), ContentType::Prefab),
+                ("Worlds".to_string(), ContentType::World),
+            ]),
+        }
+    }
+}
+
+impl RoutingTable {
+    /// Folder `content_type` is installed into, relative to the game folder.
+    pub fn folder(&self, content_type: ContentType) -> PathBuf {
+        self.folders.get(&content_type).cloned().unwrap_or_else(|| content_type.default_folder())
+    }
+
+    pub fn dir(&self, hytale_folder: &Path, content_type: ContentType) -> PathBuf {
+        hytale_folder.join(self.folder(content_type))
+    }
+
+    /// Every folder something may be installed into, without duplicates.
+    pub fn dirs(&self, hytale_folder: &Path) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for content_type in ContentType::ALL {
+            let dir = self.dir(hytale_folder, content_type);
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        dirs
+    }
+
+    /// Content type of a project filed under `class_id` (CurseForge) or
+    /// `categories` (Modtale).
+    pub fn classify(&self, api_provider: &ApiProvider, class_id: Option<&str>, categories: &[String]) -> ContentType {
+        match api_provider {
+            ApiProvider::CurseForge => class_id
+                .and_then(|id| self.curseforge_classes.get(id).copied())
+                .unwrap_or_default(),
+            ApiProvider::Modtale => categories.iter()
+                .find_map(|category| self.modtale_categories.iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(category))
+                    .map(|(_, content_type)| *content_type))
+                .unwrap_or_default(),
+            ApiProvider::Local => ContentType::Mod,
+        }
+    }
+
+    pub fn classify_mod(&self, ui_mod: &UiMod) -> ContentType {
+        self.classify(&ui_mod.provider, ui_mod.class_id.as_deref(), &ui_mod.categories)
+    }
+}
@@ -2,15 +2,50 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use std::io;
+use std::io::{self, Write};
 
-use crate::api::curse_forge_api::set_global_api_key as set_curseforge_key;
-use crate::api::mod_tale_api::set_global_api_key as set_modtale_key;
+use crate::api::local_mods::installed_path;
+use crate::api::provider::{self, BaseUrls};
+use crate::api::queue::{install_queue, DEFAULT_PARALLEL_DOWNLOADS};
+use crate::api::routing::{ContentType, RoutingTable};
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Folder the app's files live in, under the platform's config and data folders.
+const APP_DIR_NAME: &str = "hytale-mod-manager";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
 pub enum ApiProvider {
     Modtale,
     CurseForge,
+    /// Installed from a file on disk; there is no API behind it.
+    Local,
+}
+
+impl ApiProvider {
+    /// Stable lowercase id used in CLI arguments and environment variables.
+    pub fn id(&self) -> &'static str {
+        match self {
+            ApiProvider::Modtale => "modtale",
+            ApiProvider::CurseForge => "curseforge",
+            ApiProvider::Local => "local",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id.to_lowercase().as_str() {
+            "modtale" => Some(ApiProvider::Modtale),
+            "curseforge" => Some(ApiProvider::CurseForge),
+            "local" => Some(ApiProvider::Local),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ApiProvider::Modtale => "ModTale",
+            ApiProvider::CurseForge => "CurseForge",
+            ApiProvider::Local => "Local",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -19,51 +54,117 @@ pub enum AppTheme {
     Light,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct InstalledModEntry {
     pub mod_id: String,
     pub mod_name: String,
     pub file_id: String,
     pub version_name: String,
     pub api_provider: ApiProvider,
+    /// Ids of the mods the installed file requires, on the same provider.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Installed automatically to satisfy another mod's requirements.
+    #[serde(default)]
+    pub is_dependency: bool,
+    /// Ids of the mods the installed file is marked incompatible with.
+    #[serde(default)]
+    pub incompatible: Vec<String>,
+    /// Kept in the holding folder instead of `Mods`, so the game skips it.
+    #[serde(default)]
+    pub disabled: bool,
+    /// Decides which folder the file is installed into.
+    #[serde(default)]
+    pub content_type: ContentType,
+}
+
+impl InstalledModEntry {
+    /// Whether this mod lists `other` among its dependencies.
+    pub fn requires(&self, other: &InstalledModEntry) -> bool {
+        self.api_provider == other.api_provider && self.dependencies.contains(&other.mod_id)
+    }
+
+    /// Whether either mod is marked incompatible with the other.
+    pub fn conflicts_with(&self, other: &InstalledModEntry) -> bool {
+        self.api_provider == other.api_provider
+            && (self.incompatible.contains(&other.mod_id) || other.incompatible.contains(&self.mod_id))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppSettings {
+    /// Single key from before keys were stored per provider, migrated on load.
+    #[serde(default, skip_serializing)]
     pub api_key: Option<String>,
+    #[serde(default)]
+    pub api_keys: HashMap<ApiProvider, String>,
     pub game_folder: Option<PathBuf>,
     pub theme: AppTheme,
     pub api_provider: ApiProvider,
+    #[serde(default)]
+    pub federated_search: bool,
+    #[serde(default)]
+    pub base_urls: HashMap<ApiProvider, BaseUrls>,
+    /// Hytale version searches and updates target, `None` for any.
+    #[serde(default)]
+    pub target_game_version: Option<String>,
+    /// How many downloads the install queue runs at once, `None` for the default.
+    #[serde(default)]
+    pub max_parallel_downloads: Option<usize>,
+    /// Folder watched for files downloaded by hand, `None` for the system's
+    /// downloads folder.
+    #[serde(default)]
+    pub manual_download_dir: Option<PathBuf>,
+    /// Where each kind of resource is installed.
+    #[serde(default)]
+    pub routes: RoutingTable,
     pub installed_mods: HashMap<String, InstalledModEntry>,
+    /// File the settings were loaded from and are saved to.
+    #[serde(skip)]
+    pub config_path: Option<PathBuf>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             api_key: None,
+            api_keys: HashMap::new(),
             game_folder: None,
             theme: AppTheme::Dark,
             api_provider: ApiProvider::CurseForge,
+            federated_search: false,
+            base_urls: HashMap::new(),
+            target_game_version: None,
+            max_parallel_downloads: None,
+            manual_download_dir: None,
+            routes: RoutingTable::default(),
             installed_mods: HashMap::new(),
+            config_path: None,
         }
     }
 }
 
 impl AppSettings {
     pub fn load() -> Self {
-        let path = Self::get_config_path();
+        Self::load_from(Self::default_config_path())
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
         if path.exists() {
             match fs::read_to_string(&path) {
                 Ok(content) => {
                     match serde_json::from_str::<AppSettings>(&content) {
-                        Ok(settings) => {
-                            println!("Loaded settings from: {:?}", path);
+                        Ok(mut settings) => {
+                            eprintln!("Loaded settings from: {:?}", path);
 
-                            let key = settings.api_key.as_deref().unwrap_or_default();
-                            match settings.api_provider {
-                                ApiProvider::CurseForge => set_curseforge_key(key),
-                                ApiProvider::Modtale => set_modtale_key(key)
+                            settings.config_path = Some(path);
+                            if let Some(key) = settings.api_key.take() {
+                                settings.api_keys.entry(settings.api_provider.clone()).or_insert(key);
                             }
+                            settings.apply_api_keys();
+                            settings.apply_base_urls();
+                            settings.apply_game_version();
+                            settings.apply_download_limit();
                             return settings;
                         },
                         Err(e) => eprintln!("Failed to parse settings.json: {}. Using defaults.", e),
@@ -72,70 +173,181 @@ impl AppSettings {
                 Err(e) => eprintln!("Failed to read settings file: {}. Using defaults.", e),
             }
         } else {
-            let default_settings = AppSettings::default();
+            let default_settings = AppSettings { config_path: Some(path), ..AppSettings::default() };
             if let Err(e) = default_settings.save() {
                 eprintln!("Failed to create initial settings file: {}", e);
             }
+            default_settings.apply_base_urls();
+            default_settings.apply_game_version();
+            default_settings.apply_download_limit();
             return default_settings;
         }
 
-        AppSettings::default()
+        let default_settings = AppSettings { config_path: Some(path), ..AppSettings::default() };
+        default_settings.apply_base_urls();
+        default_settings.apply_game_version();
+        default_settings.apply_download_limit();
+        default_settings
     }
 
     pub fn save(&self) -> io::Result<()> {
-        let path = Self::get_config_path();
+        let path = self.get_config_path();
 
-        let key = self.api_key.as_deref().unwrap_or_default();
-        match self.api_provider {
-            ApiProvider::CurseForge => set_curseforge_key(key),
-            ApiProvider::Modtale => set_modtale_key(key)
-        }
+        self.apply_api_keys();
 
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        // Written to a temporary file and renamed over the old one, so a crash
+        // mid-save never leaves a truncated settings file behind.
         let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json)?;
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let result = fs::File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
 
-        Ok(())
+    pub fn get_config_path(&self) -> PathBuf {
+        self.config_path.clone().unwrap_or_else(Self::default_config_path)
     }
 
-    fn get_config_path() -> PathBuf {
+    fn default_config_path() -> PathBuf {
         let mut path = dirs::config_dir().unwrap_or_else(|| {
             PathBuf::from(".")
         });
 
-        path.push("hytale-mod-manager");
+        path.push(APP_DIR_NAME);
         path.push("settings.json");
         path
     }
 
-    pub fn change_api(&mut self, api_provider: ApiProvider, api_key: String) {
+    /// Where the app keeps files that aren't settings, such as archived mods:
+    /// the user's data folder, or next to a settings file given explicitly
+    /// so that it stays self-contained.
+    pub fn get_data_dir(&self) -> PathBuf {
+        match &self.config_path {
+            Some(path) if *path != Self::default_config_path() => {
+                path.parent().map(PathBuf::from).unwrap_or_default()
+            }
+            _ => dirs::data_dir().unwrap_or_else(|| PathBuf::from(".")).join(APP_DIR_NAME),
+        }
+    }
+
+    /// Configures the HTTP client of every provider that has a stored key.
+    pub fn apply_api_keys(&self) {
+        for (api_provider, key) in &self.api_keys {
+            provider::set_api_key(api_provider, key);
+        }
+    }
+
+    /// Base URLs for a provider: `HMM_<PROVIDER>_API_URL` / `HMM_<PROVIDER>_CDN_URL`
+    /// take precedence over the values stored in settings.
+    pub fn effective_base_urls(&self, api_provider: &ApiProvider) -> BaseUrls {
+        let stored = self.base_urls.get(api_provider).cloned().unwrap_or_default();
+        let from_env = |kind: &str| {
+            std::env::var(format!("HMM_{}_{}_URL", api_provider.id().to_uppercase(), kind))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        };
+
+        BaseUrls {
+            api: from_env("API").or(stored.api),
+            cdn: from_env("CDN").or(stored.cdn),
+        }
+    }
 
-        match api_provider {
-            ApiProvider::CurseForge => set_curseforge_key(&api_key),
-            ApiProvider::Modtale => set_modtale_key(&api_key)
+    pub fn apply_base_urls(&self) {
+        for p in provider::registry().all() {
+            p.set_base_urls(&self.effective_base_urls(&p.id()));
         }
+    }
 
+    pub fn set_base_urls(&mut self, api_provider: ApiProvider, urls: BaseUrls) {
+        if urls == BaseUrls::default() {
+            self.base_urls.remove(&api_provider);
+        } else {
+            self.base_urls.insert(api_provider, urls);
+        }
+        self.apply_base_urls();
+    }
+
+    pub fn change_api(&mut self, api_provider: ApiProvider, api_key: String) {
+
+        provider::set_api_key(&api_provider, &api_key);
+
+        self.api_keys.insert(api_provider.clone(), api_key);
         self.api_provider = api_provider;
-        self.api_key = Some(api_key);
     }
 
     pub fn get_api_key(&self) -> Option<String> {
-        self.api_key.clone()
+        self.get_api_key_for(&self.api_provider)
+    }
+    pub fn get_api_key_for(&self, api_provider: &ApiProvider) -> Option<String> {
+        self.api_keys.get(api_provider).cloned()
     }
     pub fn get_api_provider(&self) -> ApiProvider {
         self.api_provider.clone()
     }
 
     pub fn set_api_key(&mut self, api_key: String) {
-        self.api_key = Some(api_key);
+        provider::set_api_key(&self.api_provider, &api_key);
+        self.api_keys.insert(self.api_provider.clone(), api_key);
     }
     pub fn set_api_provider(&mut self, api_provider: ApiProvider) {
         self.api_provider = api_provider;
     }
 
+    pub fn is_federated_search(&self) -> bool {
+        self.federated_search
+    }
+    pub fn set_federated_search(&mut self, enabled: bool) {
+        self.federated_search = enabled;
+    }
+
+    pub fn apply_game_version(&self) {
+        provider::set_game_version(self.target_game_version.as_deref());
+    }
+    pub fn get_target_game_version(&self) -> Option<String> {
+        self.target_game_version.clone()
+    }
+    /// Blank values clear the target.
+    pub fn set_target_game_version(&mut self, version: Option<String>) {
+        self.target_game_version = version
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        self.apply_game_version();
+    }
+
+    pub fn apply_download_limit(&self) {
+        install_queue().set_limit(self.get_max_parallel_downloads());
+    }
+    pub fn get_max_parallel_downloads(&self) -> usize {
+        self.max_parallel_downloads.unwrap_or(DEFAULT_PARALLEL_DOWNLOADS)
+    }
+    /// `None` or zero restores the default.
+    pub fn set_max_parallel_downloads(&mut self, limit: Option<usize>) {
+        self.max_parallel_downloads = limit.filter(|n| *n > 0);
+        self.apply_download_limit();
+    }
+
+    pub fn get_manual_download_dir(&self) -> Option<PathBuf> {
+        self.manual_download_dir.clone().or_else(dirs::download_dir)
+    }
+    pub fn set_manual_download_dir(&mut self, dir: Option<PathBuf>) {
+        self.manual_download_dir = dir;
+    }
+
     pub fn get_game_folder(&self) -> Option<PathBuf> {
         self.game_folder.clone()
     }
@@ -172,12 +384,10 @@ impl AppSettings {
 
     pub fn prune_manifest(&mut self) {
         if let Some(folder) = &self.game_folder {
-            let mods_path = folder.join("UserData").join("Mods");
-
             let keys_to_remove: Vec<String> = self.installed_mods
-                .keys()
-                .filter(|filename| !mods_path.join(filename).exists())
-                .cloned()
+                .iter()
+                .filter(|(filename, entry)| !installed_path(folder, filename, Some(entry), &self.routes).exists())
+                .map(|(filename, _)| filename.clone())
                 .collect();
 
             if !keys_to_remove.is_empty() {
@@ -1,9 +1,15 @@
-use crate::api::{curse_forge_api, mod_tale_api};
-use crate::api::curse_forge_api::{ApiFile, CurseForgeMod, ModFile};
+use crate::api::checksum::{self, FileChecksum, HashAlgo};
+use crate::api::curse_forge_api::{ApiFile, Category, CurseForgeMod, FileDependency, FileHash, ModFile};
+use crate::api::download::{resume_offset, stream_to_file, DownloadedFile, ProgressSender, StreamedFile, RESUME_ATTEMPTS};
 use crate::api::mod_tale_api::{ModTaleFile, ModTaleMod};
+use crate::api::provider::provider;
 use crate::api::settings::{ApiProvider, AppSettings};
+use futures::future::join_all;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct UiModVersion {
     pub file_id: String,
     pub display_name: String,
@@ -12,6 +18,58 @@ pub struct UiModVersion {
     pub release_type: u8,
     pub game_versions: Vec<String>,
     pub upload_date: String,
+    /// Size the provider reports, used to catch truncated downloads.
+    pub file_size: Option<u64>,
+    pub checksums: Vec<FileChecksum>,
+    /// Other mods on the same provider this file relies on or conflicts with.
+    pub dependencies: Vec<ModDependency>,
+}
+
+/// How a file relates to another mod, after CurseForge's `relationType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    EmbeddedLibrary,
+    Optional,
+    Required,
+    Tool,
+    Incompatible,
+    Include,
+}
+
+impl DependencyKind {
+    pub fn from_curseforge(relation_type: i32) -> Option<Self> {
+        match relation_type {
+            1 => Some(DependencyKind::EmbeddedLibrary),
+            2 => Some(DependencyKind::Optional),
+            3 => Some(DependencyKind::Required),
+            4 => Some(DependencyKind::Tool),
+            5 => Some(DependencyKind::Incompatible),
+            6 => Some(DependencyKind::Include),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModDependency {
+    pub mod_id: String,
+    pub kind: DependencyKind,
+}
+
+fn curseforge_dependencies(dependencies: &[FileDependency]) -> Vec<ModDependency> {
+    dependencies.iter()
+        .filter_map(|d| Some(ModDependency {
+            mod_id: d.mod_id.to_string(),
+            kind: DependencyKind::from_curseforge(d.relation_type)?,
+        }))
+        .collect()
+}
+
+fn curseforge_checksums(hashes: &[FileHash]) -> Vec<FileChecksum> {
+    hashes.iter()
+        .filter_map(|h| FileChecksum::new(HashAlgo::from_curseforge(h.algo)?, &h.value))
+        .collect()
 }
 
 impl UiModVersion {
@@ -24,6 +82,9 @@ impl UiModVersion {
             release_type: file.release_type as u8,
             game_versions: file.game_versions.clone(),
             upload_date: file.file_date.clone(),
+            file_size: Some(file.file_length),
+            checksums: curseforge_checksums(&file.hashes),
+            dependencies: curseforge_dependencies(&file.dependencies),
         }
     }
 
@@ -33,9 +94,12 @@ impl UiModVersion {
             display_name: file.display_name.clone(),
             file_name: file.file_name.clone(),
             download_url: file.download_url.clone(),
-            release_type: file.release_type as u8,
+            release_type: file.release_type,
             game_versions: file.game_versions.clone(),
             upload_date: file.file_date.clone(),
+            file_size: Some(file.file_length),
+            checksums: curseforge_checksums(&file.hashes),
+            dependencies: curseforge_dependencies(&file.dependencies),
         }
     }
 
@@ -47,7 +111,7 @@ impl UiModVersion {
         };
 
         let file_name = file.download_url.as_ref()
-            .and_then(|url| url.split('/').last())
+            .and_then(|url| url.split('/').next_back())
             .map(|s| s.to_string())
             .unwrap_or_else(|| format!("{}.jar", file.version_number));
 
@@ -59,14 +123,107 @@ impl UiModVersion {
             release_type,
             game_versions: file.supported_versions.clone(),
             upload_date: file.created_at.clone(),
+            file_size: file.size,
+            checksums: [
+                (HashAlgo::Sha256, &file.sha256),
+                (HashAlgo::Sha1, &file.sha1),
+                (HashAlgo::Md5, &file.md5),
+            ]
+                .into_iter()
+                .filter_map(|(algo, value)| FileChecksum::new(algo, value.as_deref()?))
+                .collect(),
+            dependencies: file.dependencies.iter()
+                .map(|d| ModDependency {
+                    mod_id: d.mod_id.clone(),
+                    kind: if d.optional { DependencyKind::Optional } else { DependencyKind::Required },
+                })
+                .collect(),
         }
     }
+
+    pub fn supports_game_version(&self, target: &str) -> bool {
+        supports_game_version(&self.game_versions, target)
+    }
+
+    /// Ids of the mods that must be installed alongside this file.
+    pub fn required_mod_ids(&self) -> Vec<String> {
+        self.dependencies.iter()
+            .filter(|d| d.kind == DependencyKind::Required)
+            .map(|d| d.mod_id.clone())
+            .collect()
+    }
+
+    /// Ids of the mods this file is known not to work with.
+    pub fn incompatible_mod_ids(&self) -> Vec<String> {
+        self.dependencies.iter()
+            .filter(|d| d.kind == DependencyKind::Incompatible)
+            .map(|d| d.mod_id.clone())
+            .collect()
+    }
+
+    /// Fails when `data` does not match the size or checksums the provider
+    /// published for this file.
+    pub fn verify(&self, data: &[u8]) -> Result<(), String> {
+        checksum::verify(data, self.file_size, &self.checksums)
+            .map_err(|e| format!("{} failed verification: {}", self.file_name, e))
+    }
+}
+
+/// A slice of a mod's file history, newest first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionPage {
+    pub versions: Vec<UiModVersion>,
+    pub offset: u32,
+    /// Number of files the provider knows of, when it reports one.
+    pub total_count: Option<u32>,
+    pub has_more: bool,
+}
+
+fn normalize_game_version(version: &str) -> String {
+    let version = version.trim().to_lowercase();
+    version.strip_prefix('v').map(str::to_string).unwrap_or(version)
+}
+
+/// Whether a file listing `game_versions` runs on `target`. "0.2" matches
+/// "0.2" and "0.2.1", but not "0.20". An empty target matches everything.
+pub fn supports_game_version(game_versions: &[String], target: &str) -> bool {
+    let target = normalize_game_version(target);
+    if target.is_empty() {
+        return true;
+    }
+
+    game_versions.iter()
+        .map(|v| normalize_game_version(v))
+        .any(|v| v == target || v.strip_prefix(&target).is_some_and(|rest| rest.starts_with('.')))
+}
+
+/// The newest file compatible with `game_version`, preferring releases over
+/// betas and alphas.
+pub fn pick_version(
+    files: impl IntoIterator<Item = UiModVersion>,
+    game_version: Option<&str>,
+) -> Option<UiModVersion> {
+    files.into_iter()
+        .filter(|f| game_version.is_none_or(|target| f.supports_game_version(target)))
+        .max_by(|a, b| {
+            (a.release_type == 1).cmp(&(b.release_type == 1))
+                .then_with(|| a.upload_date.cmp(&b.upload_date))
+        })
+}
+
+/// Another provider offering the same mod, found by federated search.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModSource {
+    pub provider: ApiProvider,
+    pub mod_id: String,
+    pub website_url: String,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct UiMod {
     pub id: String,
     pub name: String,
+    pub slug: String,
     pub summary: String,
     pub authors: String,
     pub download_count: u64,
@@ -75,11 +232,21 @@ pub struct UiMod {
     pub banner: String,
     pub gallery_urls: Vec<String>,
     pub website_url: String,
+    /// Class the project is filed under on CurseForge, which decides where it
+    /// is installed. Modtale files projects by category only.
+    pub class_id: Option<String>,
     pub version: UiModVersion,
+    pub provider: ApiProvider,
+    pub other_sources: Vec<ModSource>,
+    /// False when the author only allows downloads from the provider's own
+    /// website, so files come without a download URL.
+    pub distribution_allowed: bool,
 }
 
 impl UiMod {
-    pub fn from_curseforge_api(api_mod: &CurseForgeMod) -> Self {
+    /// `game_version` is the target Hytale version; the newest file built
+    /// for it becomes `version`.
+    pub fn from_curseforge_api(api_mod: &CurseForgeMod, game_version: Option<&str>) -> Self {
         let authors = api_mod.authors.iter()
             .map(|a| a.name.clone())
             .collect::<Vec<_>>()
@@ -103,20 +270,13 @@ impl UiMod {
             .take(3)
             .collect();
 
-        let version = if let Some(latest) = api_mod
-            .latest_files
-            .iter()
-            .find(|v| v.release_type == 1)
-            .or(api_mod.latest_files.first())
-        {
-            UiModVersion::from_curseforge_mod_file(latest)
-        } else {
-            UiModVersion_dummy()
-        };
+        let files = api_mod.latest_files.iter().map(UiModVersion::from_curseforge_mod_file);
+        let version = pick_version(files, game_version).unwrap_or_else(UiModVersion_dummy);
 
         Self {
             id: api_mod.id.to_string(),
             name: api_mod.name.clone(),
+            slug: api_mod.slug.clone(),
             summary: api_mod.summary.clone(),
             authors,
             download_count: api_mod.download_count as u64,
@@ -125,29 +285,27 @@ impl UiMod {
             banner,
             gallery_urls,
             website_url: api_mod.links.website_url.clone(),
-            version
+            class_id: api_mod.class_id.map(|id| id.to_string()),
+            version,
+            provider: ApiProvider::CurseForge,
+            other_sources: vec![],
+            distribution_allowed: api_mod.allow_mod_distribution != Some(false),
         }
     }
 
-    pub fn from_modtale_api(modtale_mod: &ModTaleMod) -> Self {
+    pub fn from_modtale_api(modtale_mod: &ModTaleMod, game_version: Option<&str>) -> Self {
         let icon = modtale_mod.icon_url.clone().unwrap_or_default();
         let banner = modtale_mod.banner_url.clone().unwrap_or(icon.clone());
 
-        let version = if let Some(versions) = &modtale_mod.versions {
-            if let Some(first) = versions.first() {
-                UiModVersion::from_modtale_file(first)
-            } else {
-                UiModVersion_dummy()
-            }
-        } else {
-            UiModVersion_dummy()
-        };
+        let files = modtale_mod.versions.iter().flatten().map(UiModVersion::from_modtale_file);
+        let version = pick_version(files, game_version).unwrap_or_else(UiModVersion_dummy);
 
         let slug = modtale_mod.slug.clone().unwrap_or_else(|| modtale_mod.id.clone());
 
         Self {
             id: modtale_mod.id.clone(),
             name: modtale_mod.name.clone(),
+            slug: slug.clone(),
             summary: modtale_mod.summary.clone().unwrap_or_default(),
             authors: modtale_mod.author.clone(),
             download_count: modtale_mod.download_count,
@@ -156,11 +314,90 @@ impl UiMod {
             banner,
             gallery_urls: vec![],
             website_url: format!("https://modtale.net/project/{}", slug),
+            class_id: None,
             version,
+            provider: ApiProvider::Modtale,
+            other_sources: vec![],
+            distribution_allowed: true,
+        }
+    }
+
+    pub fn as_source(&self) -> ModSource {
+        ModSource {
+            provider: self.provider.clone(),
+            mod_id: self.id.clone(),
+            website_url: self.website_url.clone(),
+        }
+    }
+
+    /// Whether `version` has to be fetched by hand from the provider's website.
+    pub fn needs_manual_download(&self, version: &UiModVersion) -> bool {
+        !self.distribution_allowed || (version.download_url.is_none() && !version.file_id.is_empty())
+    }
+
+    /// Every provider offering this mod, starting with the one it installs from.
+    pub fn providers(&self) -> Vec<ApiProvider> {
+        std::iter::once(self.provider.clone())
+            .chain(self.other_sources.iter().map(|s| s.provider.clone()))
+            .collect()
+    }
+}
+
+/// A category or class a provider files its mods under. Classes are the
+/// top level ("Mods", "Worlds"); every other category belongs to one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UiCategory {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub icon: String,
+    pub is_class: bool,
+    pub class_id: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+impl UiCategory {
+    pub fn from_curseforge_api(category: &Category) -> Self {
+        Self {
+            id: category.id.to_string(),
+            name: category.name.clone(),
+            slug: category.slug.clone(),
+            icon: category.icon_url.clone(),
+            is_class: category.is_class.unwrap_or(false),
+            class_id: category.class_id.map(|id| id.to_string()),
+            parent_id: category.parent_category_id.map(|id| id.to_string()),
+        }
+    }
+
+    /// Whether `value` names this category by id, slug or display name.
+    pub fn matches(&self, value: &str) -> bool {
+        self.id == value || self.slug.eq_ignore_ascii_case(value) || self.name.eq_ignore_ascii_case(value)
+    }
+
+    /// The filter that restricts a search to this category.
+    pub fn as_filter(&self) -> SearchFilter {
+        if self.is_class {
+            SearchFilter { category_id: None, class_id: Some(self.id.clone()) }
+        } else {
+            SearchFilter { category_id: Some(self.id.clone()), class_id: self.class_id.clone() }
         }
     }
 }
 
+/// Optional restrictions for a search. Ids come from the provider's own
+/// `categories()` list.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchFilter {
+    pub category_id: Option<String>,
+    pub class_id: Option<String>,
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self.category_id.is_none() && self.class_id.is_none()
+    }
+}
+
 fn UiModVersion_dummy() -> UiModVersion {
     UiModVersion {
         file_id: "".to_string(),
@@ -170,6 +407,9 @@ fn UiModVersion_dummy() -> UiModVersion {
         release_type: 0,
         game_versions: vec![],
         upload_date: "".to_string(),
+        file_size: None,
+        checksums: vec![],
+        dependencies: vec![],
     }
 }
 
@@ -178,144 +418,159 @@ pub async fn search_mods_unified(
     sort: u32,
     query: String,
     offset: u32,
+    filter: &SearchFilter,
 ) -> Result<(Vec<UiMod>, u32), String> {
-    match settings.api_provider {
-        ApiProvider::CurseForge => {
-            match curse_forge_api::search_mods(query, sort, offset).await {
-                Ok((api_mods, pagination)) => {
-                    let ui_mods: Vec<UiMod> = api_mods.iter()
-                        .map(|m| UiMod::from_curseforge_api(m))
-                        .collect();
-
-                    let total_pages = if let Some(p) = pagination {
-                        (p.total_count as f64 / p.page_size as f64).ceil() as u32
-                    } else {
-                        0
-                    };
-
-                    Ok((ui_mods, total_pages))
-                }
-                Err(e) => Err(e),
-            }
-        }
-        ApiProvider::Modtale => {
-            let sort = match sort {
-                1 => "relevance",
-                2 => "downloads",
-                3 => "updated",
-                _ => "downloads",
-            };
-            match mod_tale_api::search_mods(query, sort, offset).await {
-                Ok((api_mods, meta)) => {
-                    let ui_mods: Vec<UiMod> = api_mods.iter()
-                        .map(|m| UiMod::from_modtale_api(m))
-                        .collect();
-
-                    let total_pages = if let Some(m) = meta {
-                        m.last_page
-                    } else {
-                        0
-                    };
-
-                    Ok((ui_mods, total_pages))
-                }
-                Err(e) => Err(e),
-            }
-        }
-    }
+    provider(&settings.api_provider)?
+        .search(query, sort, offset, filter)
+        .await
+}
+
+pub async fn get_categories_unified(api_provider: &ApiProvider) -> Result<Vec<UiCategory>, String> {
+    provider(api_provider)?
+        .categories()
+        .await
 }
 
 pub async fn get_mod_versions_unified(
-    settings: &AppSettings,
+    api_provider: &ApiProvider,
     mod_id: &str,
 ) -> Result<Vec<UiModVersion>, String> {
-    match settings.api_provider {
-        ApiProvider::CurseForge => {
-            let cf_id = mod_id.parse::<u32>()
-                .map_err(|_| "Invalid ID format for CurseForge (expected number)".to_string())?;
-
-            match curse_forge_api::get_mod_files(cf_id).await {
-                Ok(files) => {
-                    let versions = files.iter()
-                        .map(|f| UiModVersion::from_curseforge_api_file(f))
-                        .collect();
-                    Ok(versions)
-                }
-                Err(e) => {
-                    println!("CurseForge Versions fetch FAILED: {}", e);
-                    Err(e.to_string())
-                }
-            }
-        }
-        ApiProvider::Modtale => {
-            match mod_tale_api::get_mod_files(mod_id).await {
-                Ok(files) => {
-                    let versions = files.iter()
-                        .map(|f| UiModVersion::from_modtale_file(f))
-                        .collect();
-                    Ok(versions)
-                }
-                Err(e) => {
-                    println!("ModTale Versions fetch FAILED: {}", e);
-                    Err(e)
-                }
-            }
-        }
-    }
+    provider(api_provider)?
+        .get_versions(mod_id)
+        .await
+}
+
+pub async fn get_mod_versions_page_unified(
+    api_provider: &ApiProvider,
+    mod_id: &str,
+    offset: u32,
+) -> Result<VersionPage, String> {
+    provider(api_provider)?
+        .get_versions_page(mod_id, offset)
+        .await
 }
 
 pub async fn download_version_unified(
-    settings: &AppSettings,
+    api_provider: &ApiProvider,
     version: &UiModVersion,
 ) -> Result<(String, Vec<u8>), String> {
     let url = version.download_url.as_ref()
         .ok_or("No download URL available")?;
 
-    match settings.api_provider {
-        ApiProvider::CurseForge => curse_forge_api::download_url(url).await.map(|bytes| (version.file_name.clone(), bytes)),
-        ApiProvider::Modtale => {
-            match mod_tale_api::download_url(url).await {
-                Ok(bytes) => {
-                    Ok((version.file_name.clone(), bytes))
-                }
-                Err(e) => {
-                    println!("ModTale Download FAILED: {}", e);
-                    Err(e)
+    // Verified before anything touches the Mods folder, so a bad download
+    // never replaces a working file.
+    match provider(api_provider)?.download(url).await.and_then(|bytes| version.verify(&bytes).map(|_| bytes)) {
+        Ok(bytes) => Ok((version.file_name.clone(), bytes)),
+        Err(e) => {
+            eprintln!("{:?} Download FAILED: {}", api_provider, e);
+            Err(e)
+        }
+    }
+}
+
+/// Streams a version into the downloads folder beside `Mods`, reporting
+/// progress on `progress` and verifying it before returning. Nothing in the
+/// Mods folder changes until the result is passed to `install_downloaded_mod`.
+pub async fn download_version_to_file_unified(
+    api_provider: &ApiProvider,
+    version: &UiModVersion,
+    hytale_folder: &Path,
+    progress: Option<&ProgressSender>,
+) -> Result<DownloadedFile, String> {
+    let result = stream_version_unified(api_provider, version, hytale_folder, progress).await
+        .and_then(|streamed| streamed.verify(version));
+
+    if let Err(e) = &result {
+        eprintln!("{:?} Download FAILED: {}", api_provider, e);
+    }
+    result
+}
+
+/// The streaming half of `download_version_to_file_unified`, leaving the
+/// verification step to the caller. A transfer that breaks off after making
+/// progress is resumed from the staged bytes, up to `RESUME_ATTEMPTS` times.
+pub async fn stream_version_unified(
+    api_provider: &ApiProvider,
+    version: &UiModVersion,
+    hytale_folder: &Path,
+    progress: Option<&ProgressSender>,
+) -> Result<StreamedFile, String> {
+    let url = version.download_url.as_ref()
+        .ok_or("No download URL available")?;
+    let api = provider(api_provider)?;
+
+    let mut attempt = 0;
+    loop {
+        let offset = resume_offset(hytale_folder, version);
+        if offset > 0 {
+            eprintln!("Resuming {} from byte {}", version.file_name, offset);
+        }
+
+        let result = match api.open_download(url, offset).await {
+            Ok(resp) => stream_to_file(resp, hytale_folder, version, offset, progress).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Err(e) if attempt < RESUME_ATTEMPTS && resume_offset(hytale_folder, version) > offset => {
+                eprintln!("{} interrupted: {}", version.file_name, e);
+                attempt += 1;
+            }
+            other => return other,
+        }
+    }
+}
+
+/// Details for mods from any mix of providers, keyed by provider and mod id.
+/// Each provider gets one batch and the providers are queried concurrently;
+/// mods that fail to load are simply absent from the map.
+pub async fn get_mod_details_batch_unified(
+    mods: &[(ApiProvider, String)],
+) -> HashMap<(ApiProvider, String), UiMod> {
+    let mut by_provider: HashMap<ApiProvider, Vec<String>> = HashMap::new();
+    for (api_provider, mod_id) in mods {
+        let ids = by_provider.entry(api_provider.clone()).or_default();
+        if !ids.contains(mod_id) {
+            ids.push(mod_id.clone());
+        }
+    }
+
+    let batches = by_provider.into_iter().map(|(api_provider, ids)| async move {
+        let result = match provider(&api_provider) {
+            Ok(p) => p.get_details_batch(&ids).await,
+            Err(e) => Err(e),
+        };
+        (api_provider, result)
+    });
+
+    let mut details = HashMap::new();
+    for (api_provider, result) in join_all(batches).await {
+        match result {
+            Ok(ui_mods) => {
+                for ui_mod in ui_mods {
+                    details.insert((api_provider.clone(), ui_mod.id.clone()), ui_mod);
                 }
             }
+            Err(e) => eprintln!("{:?} batch fetch FAILED: {}", api_provider, e),
         }
     }
+    details
 }
 
 pub async fn get_mod_details_unified(
-    provider: &ApiProvider,
+    api_provider: &ApiProvider,
     mod_id: &str,
 ) -> Option<UiMod> {
-    match provider {
-        ApiProvider::CurseForge => {
-            if let Ok(id_num) = mod_id.parse::<u32>() {
-                match curse_forge_api::get_mod(id_num).await {
-                    Ok(m) => Some(UiMod::from_curseforge_api(&m)),
-                    Err(e) => {
-                        println!("CurseForge fetch failed for {}: {}", mod_id, e);
-                        None
-                    }
-                }
-            } else {
-                println!("Invalid CurseForge ID: {}", mod_id);
-                None
-            }
-        }
-        ApiProvider::Modtale => {
-            match mod_tale_api::get_mod(mod_id).await {
-                Ok(m) => {
-                    Some(UiMod::from_modtale_api(&m))
-                }
-                Err(e) => {
-                    println!("ModTale fetch FAILED for {}: {}", mod_id, e);
-                    None
-                }
-            }
+    let result = match provider(api_provider) {
+        Ok(p) => p.get_details(mod_id).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(m) => Some(m),
+        Err(e) => {
+            eprintln!("{:?} fetch FAILED for {}: {}", api_provider, mod_id, e);
+            None
         }
     }
-}
\ No newline at end of file
+}
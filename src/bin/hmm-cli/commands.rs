@@ -0,0 +1,665 @@
+use std::cell::RefCell;
+use futures::future::join_all;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use hytale_mod_manager::api::archive::archived_versions;
+use hytale_mod_manager::api::dependencies::{audit_conflicts, resolve_install_plan, InstallPlan};
+use hytale_mod_manager::api::federated::search_mods_federated;
+use hytale_mod_manager::api::local_mods::{
+    check_install_status, find_dependents, find_orphans, get_mods_dir, remove_mod,
+    remove_mod_with_dependents, restore_archived, scan_installed, set_mod_enabled, set_route,
+};
+use hytale_mod_manager::api::local_file::install_from_file;
+use hytale_mod_manager::api::manual::{download_page, find_download, import_download, open_url, wait_for_download};
+use hytale_mod_manager::api::queue::{install_job, install_queue, JobId, JobStage, JobState};
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+use hytale_mod_manager::api::ui_mod::{
+    get_categories_unified, get_mod_details_batch_unified, get_mod_details_unified,
+    get_mod_versions_unified,
+    search_mods_unified, SearchFilter, UiCategory, UiMod, UiModVersion,
+};
+use crate::output::{
+    AuditReport, CategoriesReport, CliError, ConfigReport, ErrorCode, ImportReport, InfoReport, InstallFileReport, InstallOutcome,
+    InstallReport, ListReport, ListedMod, OrphansReport, RemoveReport, Report, RollbackReport, ToggleReport, SearchHit, SearchReport, UpdateReport,
+    VersionsReport,
+};
+use crate::Command;
+
+const PAGE_SIZE: u32 = 20;
+
+pub async fn run(command: Command, settings: &mut AppSettings) -> Result<Report, CliError> {
+    match command {
+        Command::Search { query, sort, page, all, category, class } => {
+            let federated = all || settings.is_federated_search();
+            let filter = resolve_filter(settings, category.as_deref(), class.as_deref()).await?;
+            search(settings, query, sort.as_index(), page, federated, filter).await
+        }
+        Command::Categories => categories(settings).await,
+        Command::Info { mod_id } => info(settings, &mod_id).await,
+        Command::Versions { mod_id } => versions(settings, &mod_id).await,
+        Command::Install { mod_id, file, no_deps, dry_run, force } => {
+            install(settings, &mod_id, file.as_deref(), !no_deps, dry_run, force).await
+        }
+        Command::InstallFile { path, content_type } => install_file(settings, &path, content_type),
+        Command::Import { mod_id, file, from, open, wait } => {
+            import(settings, &mod_id, file.as_deref(), from, open, wait).await
+        }
+        Command::Remove { target, with_dependents, force } => remove(settings, &target, with_dependents, force),
+        Command::Disable { target } => toggle(settings, &target, false),
+        Command::Enable { target } => toggle(settings, &target, true),
+        Command::List => list(settings),
+        Command::Orphans { remove } => orphans(settings, remove),
+        Command::Rollback { mod_id, file, list } => rollback(settings, &mod_id, file.as_deref(), list),
+        Command::Audit => Ok(Report::Audit(AuditReport { conflicts: audit_conflicts(settings) })),
+        Command::Update { mod_ids } => update(settings, &mod_ids).await,
+        Command::Config {
+            game_folder, provider, api_key, set_api_url, set_cdn_url, game_version, parallel_downloads,
+            manual_download_dir, route, route_class, route_category,
+        } => {
+            config(
+                settings,
+                game_folder,
+                provider.map(Into::into),
+                api_key,
+                set_api_url,
+                set_cdn_url,
+                game_version,
+                parallel_downloads,
+                manual_download_dir,
+                Routes { folders: route, classes: route_class, categories: route_category },
+            )
+        }
+    }
+}
+
+fn require_folder(settings: &AppSettings) -> Result<PathBuf, CliError> {
+    settings.get_game_folder().ok_or_else(|| CliError::new(
+        ErrorCode::NoGameFolder,
+        "No game folder set. Run `hmm-cli config --game-folder <PATH>` first.",
+    ))
+}
+
+async fn fetch_categories(settings: &AppSettings) -> Result<Vec<UiCategory>, CliError> {
+    get_categories_unified(&settings.api_provider).await
+        .map_err(|e| CliError::new(ErrorCode::ApiError, e))
+}
+
+/// Turns `--category` / `--class` values into provider ids. Category ids
+/// belong to the active provider, so they are looked up there.
+async fn resolve_filter(
+    settings: &AppSettings,
+    category: Option<&str>,
+    class: Option<&str>,
+) -> Result<SearchFilter, CliError> {
+    if category.is_none() && class.is_none() {
+        return Ok(SearchFilter::default());
+    }
+
+    let categories = fetch_categories(settings).await?;
+    let find = |value: &str, want_class: bool| {
+        categories.iter()
+            .find(|c| c.is_class == want_class && c.matches(value))
+            .ok_or_else(|| CliError::new(
+                ErrorCode::NotFound,
+                format!(
+                    "{} `{}` not found on {}",
+                    if want_class { "Class" } else { "Category" },
+                    value,
+                    settings.api_provider.display_name()
+                ),
+            ))
+    };
+
+    let mut filter = SearchFilter::default();
+    if let Some(value) = category {
+        filter = find(value, false)?.as_filter();
+    }
+    if let Some(value) = class {
+        filter.class_id = Some(find(value, true)?.id.clone());
+    }
+    Ok(filter)
+}
+
+async fn categories(settings: &AppSettings) -> Result<Report, CliError> {
+    let categories = fetch_categories(settings).await?;
+
+    Ok(Report::Categories(CategoriesReport {
+        provider: settings.get_api_provider(),
+        categories,
+    }))
+}
+
+async fn search(
+    settings: &AppSettings,
+    query: String,
+    sort: u32,
+    page: u32,
+    federated: bool,
+    filter: SearchFilter,
+) -> Result<Report, CliError> {
+    let page = page.max(1);
+    let offset = (page - 1) * PAGE_SIZE;
+    let result = if federated {
+        search_mods_federated(settings, sort, query, offset, &filter).await
+    } else {
+        search_mods_unified(settings, sort, query, offset, &filter).await
+    };
+    let (mods, total_pages) = result.map_err(|e| CliError::new(ErrorCode::ApiError, e))?;
+
+    let results = mods.into_iter()
+        .map(|m| {
+            let install = check_install_status(settings, &m.id, &m.version.file_id);
+            SearchHit { ui_mod: m, install }
+        })
+        .collect();
+
+    Ok(Report::Search(SearchReport {
+        provider: settings.get_api_provider(),
+        federated,
+        filter,
+        page,
+        total_pages,
+        results,
+    }))
+}
+
+async fn fetch_mod(settings: &AppSettings, mod_id: &str) -> Result<UiMod, CliError> {
+    get_mod_details_unified(&settings.api_provider, mod_id).await.ok_or_else(|| CliError::new(
+        ErrorCode::NotFound,
+        format!("Mod {} not found on {}", mod_id, settings.api_provider.display_name()),
+    ))
+}
+
+async fn info(settings: &AppSettings, mod_id: &str) -> Result<Report, CliError> {
+    let ui_mod = fetch_mod(settings, mod_id).await?;
+    let install = check_install_status(settings, &ui_mod.id, &ui_mod.version.file_id);
+
+    Ok(Report::Info(Box::new(InfoReport { ui_mod, install })))
+}
+
+async fn versions(settings: &AppSettings, mod_id: &str) -> Result<Report, CliError> {
+    let versions = get_mod_versions_unified(&settings.api_provider, mod_id).await
+        .map_err(|e| CliError::new(ErrorCode::ApiError, e))?;
+    let installed_file_id = settings.installed_mods.values()
+        .find(|e| e.mod_id == mod_id)
+        .map(|e| e.file_id.clone());
+
+    Ok(Report::Versions(VersionsReport {
+        mod_id: mod_id.to_string(),
+        installed_file_id,
+        target_game_version: settings.get_target_game_version(),
+        versions,
+    }))
+}
+
+/// Runs a queued job to completion.
+async fn run_job(
+    folder: &Path,
+    job_id: JobId,
+    settings: &RefCell<&mut AppSettings>,
+) -> Result<(), CliError> {
+    let state = install_queue().run(job_id, folder, |job, file| {
+        install_job(folder, job, file, &mut settings.borrow_mut())
+    }).await;
+
+    match state {
+        JobState::Done => Ok(()),
+        JobState::Failed { stage: JobStage::Install, error } => Err(CliError::new(ErrorCode::InstallFailed, error)),
+        JobState::Failed { error, .. } => Err(CliError::new(ErrorCode::DownloadFailed, error)),
+        other => Err(CliError::new(ErrorCode::DownloadFailed, format!("Download {}", other.label().to_lowercase()))),
+    }
+}
+
+async fn install(
+    settings: &mut AppSettings,
+    mod_id: &str,
+    file_id: Option<&str>,
+    with_dependencies: bool,
+    dry_run: bool,
+    force: bool,
+) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let ui_mod = fetch_mod(settings, mod_id).await?;
+    let version = resolve_version(&ui_mod, file_id).await?;
+    let manual = ui_mod.needs_manual_download(&version).then(|| download_page(&ui_mod, &version));
+
+    let plan = if with_dependencies {
+        resolve_install_plan(&ui_mod, version, settings).await
+    } else {
+        let mut plan = InstallPlan::single(&ui_mod, version);
+        plan.check_conflicts(settings);
+        plan
+    };
+    if dry_run {
+        return Ok(Report::Plan(plan));
+    }
+    if let Some(page) = manual {
+        return Err(CliError::new(
+            ErrorCode::ManualDownloadRequired,
+            format!(
+                "The author of {} only allows downloads from {}. Download it there, then run `hmm-cli import {}`",
+                ui_mod.name, page, ui_mod.id
+            ),
+        ));
+    }
+    if !plan.conflicts.is_empty() && !force {
+        let pairs: Vec<_> = plan.conflicts.iter()
+            .map(|c| format!("{} and {}", c.mod_name, c.other_mod_name))
+            .collect();
+        return Err(CliError::new(
+            ErrorCode::Incompatible,
+            format!("Incompatible mods: {}; use --force to install anyway", pairs.join(", ")),
+        ));
+    }
+
+    let previous_versions: Vec<_> = plan.installs.iter()
+        .map(|p| settings.installed_mods.values()
+            .find(|e| e.mod_id == p.ui_mod.id && e.api_provider == p.ui_mod.provider)
+            .map(|e| e.version_name.clone()))
+        .collect();
+
+    // The target and its dependencies download side by side, within the
+    // queue's parallelism limit.
+    let job_ids = plan.enqueue(install_queue());
+    let settings = RefCell::new(settings);
+    let outcomes = join_all(job_ids.iter().map(|id| run_job(&folder, *id, &settings))).await;
+
+    let mut reports = plan.installs.iter()
+        .zip(previous_versions)
+        .zip(outcomes)
+        .map(|((planned, previous_version), outcome)| {
+            let (status, error) = match outcome {
+                Ok(()) if previous_version.is_some() => (InstallOutcome::Updated, None),
+                Ok(()) => (InstallOutcome::Installed, None),
+                Err(e) => (InstallOutcome::Failed, Some(e)),
+            };
+            InstallReport {
+                mod_id: planned.ui_mod.id.clone(),
+                mod_name: planned.ui_mod.name.clone(),
+                provider: planned.ui_mod.provider.clone(),
+                status,
+                previous_version,
+                version: Some(planned.version.clone()),
+                error,
+                dependencies: vec![],
+                satisfied: vec![],
+                missing: vec![],
+                conflicts: vec![],
+            }
+        });
+
+    // Even if the target failed, its dependencies may be on disk by now, so
+    // the report still lists them; `Report::error` carries the failure.
+    let mut report = reports.next().expect("a plan always has its target");
+    report.dependencies = reports.collect();
+    report.satisfied = plan.satisfied;
+    report.missing = plan.missing;
+    report.conflicts = plan.conflicts;
+
+    Ok(Report::Install(Box::new(report)))
+}
+
+fn install_file(settings: &mut AppSettings, path: &Path, content_type: ContentType) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    if !path.is_file() {
+        return Err(CliError::new(ErrorCode::NotFound, format!("{} not found", path.display())));
+    }
+    let info = install_from_file(&folder, path, content_type, settings)
+        .map_err(|e| CliError::new(ErrorCode::InstallFailed, e))?;
+
+    Ok(Report::InstallFile(InstallFileReport { info, content_type }))
+}
+
+/// The file `file_id` names, or the latest release.
+async fn resolve_version(ui_mod: &UiMod, file_id: Option<&str>) -> Result<UiModVersion, CliError> {
+    match file_id {
+        Some(file_id) => get_mod_versions_unified(&ui_mod.provider, &ui_mod.id).await
+            .map_err(|e| CliError::new(ErrorCode::ApiError, e))?
+            .into_iter()
+            .find(|v| v.file_id == file_id)
+            .ok_or_else(|| CliError::new(
+                ErrorCode::NotFound,
+                format!("File {} not found for mod {}", file_id, ui_mod.id),
+            )),
+        None => Ok(ui_mod.version.clone()),
+    }
+}
+
+async fn import(
+    settings: &mut AppSettings,
+    mod_id: &str,
+    file_id: Option<&str>,
+    from: Option<PathBuf>,
+    open: bool,
+    wait: Option<u64>,
+) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let ui_mod = fetch_mod(settings, mod_id).await?;
+    let version = resolve_version(&ui_mod, file_id).await?;
+    let dir = from.or_else(|| settings.get_manual_download_dir()).ok_or_else(|| CliError::new(
+        ErrorCode::ConfigError,
+        "No downloads folder found. Pass --from <DIR> or run `hmm-cli config --manual-download-dir <DIR>`.",
+    ))?;
+
+    let page = download_page(&ui_mod, &version);
+    if open {
+        if let Err(e) = open_url(&page) {
+            eprintln!("Failed to open {}: {}", page, e);
+        }
+    }
+    let found = match wait {
+        Some(secs) => wait_for_download(&dir, &version, Some(Duration::from_secs(secs))).await,
+        None => find_download(&dir, &version),
+    };
+    let source = found.ok_or_else(|| CliError::new(
+        ErrorCode::NotFound,
+        format!("{} not found in {}. Download it from {}", version.file_name, dir.display(), page),
+    ))?;
+
+    import_download(&folder, &source, &ui_mod, &version, settings)
+        .map_err(|e| CliError::new(ErrorCode::InstallFailed, e))?;
+
+    Ok(Report::Import(ImportReport {
+        mod_id: ui_mod.id,
+        mod_name: ui_mod.name,
+        provider: ui_mod.provider,
+        version,
+        source,
+    }))
+}
+
+/// File name of the installed mod `target` names, by file name or mod id.
+fn resolve_installed(settings: &AppSettings, folder: &Path, target: &str) -> Result<String, CliError> {
+    if settings.installed_mods.contains_key(target) {
+        Ok(target.to_string())
+    } else if let Some((file_name, _)) = settings.installed_mods.iter().find(|(_, e)| e.mod_id == target) {
+        Ok(file_name.clone())
+    } else if get_mods_dir(folder, &settings.routes).join(target).is_file() {
+        Ok(target.to_string())
+    } else {
+        Err(CliError::new(ErrorCode::NotInstalled, format!("{} is not installed", target)))
+    }
+}
+
+fn remove(settings: &mut AppSettings, target: &str, with_dependents: bool, force: bool) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let file_name = resolve_installed(settings, &folder, target)?;
+
+    let mod_id = settings.get_installed_mod(&file_name).map(|e| e.mod_id.clone());
+    let dependents = find_dependents(settings, &file_name);
+    let dependents_removed = if with_dependents {
+        remove_mod_with_dependents(&folder, &file_name, settings)
+            .map_err(|e| CliError::new(ErrorCode::RemoveFailed, e))?
+    } else if dependents.is_empty() || force {
+        remove_mod(&folder, &file_name, settings)
+            .map_err(|e| CliError::new(ErrorCode::RemoveFailed, e))?;
+        vec![]
+    } else {
+        return Err(CliError::new(
+            ErrorCode::HasDependents,
+            format!(
+                "{} is required by {}; use --with-dependents to remove them too or --force to remove it anyway",
+                file_name,
+                dependents.join(", ")
+            ),
+        ));
+    };
+
+    Ok(Report::Remove(RemoveReport { file_name, mod_id, removed: true, dependents_removed }))
+}
+
+fn toggle(settings: &mut AppSettings, target: &str, enabled: bool) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let file_name = resolve_installed(settings, &folder, target)?;
+    let mod_id = settings.get_installed_mod(&file_name).map(|e| e.mod_id.clone());
+
+    set_mod_enabled(&folder, &file_name, enabled, settings)
+        .map_err(|e| CliError::new(ErrorCode::InstallFailed, e))?;
+
+    Ok(Report::Toggle(ToggleReport { file_name, mod_id, enabled }))
+}
+
+fn orphans(settings: &mut AppSettings, remove: bool) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let mods = find_orphans(settings).into_iter()
+        .map(|file_name| ListedMod {
+            entry: settings.get_installed_mod(&file_name).cloned(),
+            file_name,
+        })
+        .collect::<Vec<_>>();
+
+    if remove {
+        for listed in &mods {
+            remove_mod(&folder, &listed.file_name, settings)
+                .map_err(|e| CliError::new(ErrorCode::RemoveFailed, e))?;
+        }
+    }
+
+    Ok(Report::Orphans(OrphansReport { mods, removed: remove }))
+}
+
+fn rollback(settings: &mut AppSettings, mod_id: &str, file_id: Option<&str>, list_only: bool) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let api_provider = settings.installed_mods.values()
+        .find(|e| e.mod_id == mod_id)
+        .map_or_else(|| settings.get_api_provider(), |e| e.api_provider.clone());
+
+    let archived = archived_versions(settings, &api_provider, mod_id);
+    if list_only {
+        return Ok(Report::Rollback(RollbackReport { mod_id: mod_id.to_string(), restored: None, archived }));
+    }
+
+    let target = archived.iter()
+        .find(|v| file_id.is_none_or(|id| v.entry.file_id == id))
+        .cloned()
+        .ok_or_else(|| CliError::new(ErrorCode::NotFound, match file_id {
+            Some(id) => format!("File {} of mod {} is not archived", id, mod_id),
+            None => format!("No earlier version of mod {} is archived", mod_id),
+        }))?;
+    restore_archived(&folder, &target, settings)
+        .map_err(|e| CliError::new(ErrorCode::InstallFailed, e))?;
+
+    Ok(Report::Rollback(RollbackReport {
+        mod_id: mod_id.to_string(),
+        restored: Some(target),
+        archived: archived_versions(settings, &api_provider, mod_id),
+    }))
+}
+
+fn list(settings: &AppSettings) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+    let mods_dir = get_mods_dir(&folder, &settings.routes);
+
+    let mods = scan_installed(&folder, settings).into_iter()
+        .map(|file_name| ListedMod {
+            entry: settings.get_installed_mod(&file_name).cloned(),
+            file_name,
+        })
+        .collect();
+
+    Ok(Report::List(ListReport { mods_dir, mods }))
+}
+
+async fn update(settings: &mut AppSettings, mod_ids: &[String]) -> Result<Report, CliError> {
+    let folder = require_folder(settings)?;
+
+    // Mods installed from a file have no provider to check for updates.
+    let mut entries: Vec<_> = settings.installed_mods.values()
+        .filter(|e| e.api_provider != ApiProvider::Local)
+        .filter(|e| mod_ids.is_empty() || mod_ids.contains(&e.mod_id))
+        .cloned()
+        .collect();
+    entries.sort_by_key(|e| e.mod_name.to_lowercase());
+
+    let lookups: Vec<_> = entries.iter()
+        .map(|e| (e.api_provider.clone(), e.mod_id.clone()))
+        .collect();
+    let mut details = get_mod_details_batch_unified(&lookups).await;
+
+    let mut results = Vec::new();
+    let mut pending = Vec::new();
+    for entry in entries {
+        let mut report = InstallReport {
+            mod_id: entry.mod_id.clone(),
+            mod_name: entry.mod_name.clone(),
+            provider: entry.api_provider.clone(),
+            status: InstallOutcome::UpToDate,
+            previous_version: Some(entry.version_name.clone()),
+            version: None,
+            error: None,
+            dependencies: vec![],
+            satisfied: vec![],
+            missing: vec![],
+            conflicts: vec![],
+        };
+
+        let Some(latest) = details.remove(&(entry.api_provider.clone(), entry.mod_id.clone())) else {
+            report.status = InstallOutcome::Failed;
+            report.error = Some(CliError::new(ErrorCode::ApiError, "Could not fetch mod details"));
+            results.push(report);
+            continue;
+        };
+
+        if !latest.version.file_id.is_empty() && latest.version.file_id != entry.file_id {
+            report.version = Some(latest.version.clone());
+            if latest.needs_manual_download(&latest.version) {
+                report.status = InstallOutcome::Failed;
+                report.error = Some(CliError::new(
+                    ErrorCode::ManualDownloadRequired,
+                    format!(
+                        "Download it from {}, then run `hmm-cli import {}`",
+                        download_page(&latest, &latest.version), latest.id
+                    ),
+                ));
+            } else {
+                pending.push((results.len(), latest));
+            }
+        }
+        results.push(report);
+    }
+
+    // Every update goes through the queue together, so its parallelism
+    // limit decides how many download at once.
+    let settings = RefCell::new(settings);
+    let outcomes = join_all(pending.iter().map(|(index, latest)| async {
+        let job_id = install_queue().enqueue_update(latest, latest.version.clone());
+        (*index, run_job(&folder, job_id, &settings).await)
+    })).await;
+
+    for (index, outcome) in outcomes {
+        let report = &mut results[index];
+        match outcome {
+            Ok(_) => report.status = InstallOutcome::Updated,
+            Err(e) => {
+                report.status = InstallOutcome::Failed;
+                report.error = Some(e);
+            }
+        }
+    }
+
+    Ok(Report::Update(UpdateReport { results }))
+}
+
+/// Route changes requested through `config`.
+struct Routes {
+    folders: Vec<(ContentType, PathBuf)>,
+    classes: Vec<(String, ContentType)>,
+    categories: Vec<(String, ContentType)>,
+}
+
+impl Routes {
+    fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.classes.is_empty() && self.categories.is_empty()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn config(
+    settings: &mut AppSettings,
+    game_folder: Option<PathBuf>,
+    provider: Option<ApiProvider>,
+    api_key: Option<String>,
+    set_api_url: Vec<(ApiProvider, String)>,
+    set_cdn_url: Vec<(ApiProvider, String)>,
+    game_version: Option<String>,
+    parallel_downloads: Option<usize>,
+    manual_download_dir: Option<PathBuf>,
+    routes: Routes,
+) -> Result<Report, CliError> {
+    let changed = game_folder.is_some() || provider.is_some() || api_key.is_some()
+        || !set_api_url.is_empty() || !set_cdn_url.is_empty() || game_version.is_some()
+        || parallel_downloads.is_some() || manual_download_dir.is_some() || !routes.is_empty();
+
+    // Routes move files within the game folder, so a new one applies first.
+    if let Some(folder) = game_folder {
+        settings.set_game_folder(Some(folder));
+    }
+
+    for (class_id, content_type) in routes.classes {
+        settings.routes.curseforge_classes.insert(class_id, content_type);
+    }
+    for (category, content_type) in routes.categories {
+        settings.routes.modtale_categories.insert(category, content_type);
+    }
+    for (content_type, dir) in routes.folders {
+        // Moving installed files needs the game folder; without one there is nothing to move.
+        match settings.get_game_folder() {
+            Some(folder) => set_route(&folder, content_type, dir, settings)
+                .map_err(|e| CliError::new(ErrorCode::ConfigError, e))?,
+            None => {
+                settings.routes.folders.insert(content_type, dir);
+            }
+        }
+    }
+
+    if let Some(dir) = manual_download_dir {
+        settings.set_manual_download_dir(Some(dir).filter(|d| !d.as_os_str().is_empty()));
+    }
+
+    if let Some(limit) = parallel_downloads {
+        settings.set_max_parallel_downloads(Some(limit));
+    }
+
+    if let Some(version) = game_version {
+        settings.set_target_game_version(Some(version));
+    }
+
+    for (api_provider, url) in set_api_url {
+        let mut urls = settings.base_urls.get(&api_provider).cloned().unwrap_or_default();
+        urls.api = Some(url).filter(|u| !u.is_empty());
+        settings.set_base_urls(api_provider, urls);
+    }
+    for (api_provider, url) in set_cdn_url {
+        let mut urls = settings.base_urls.get(&api_provider).cloned().unwrap_or_default();
+        urls.cdn = Some(url).filter(|u| !u.is_empty());
+        settings.set_base_urls(api_provider, urls);
+    }
+
+    match (provider, api_key) {
+        (provider, Some(key)) => {
+            let provider = provider.unwrap_or_else(|| settings.get_api_provider());
+            settings.change_api(provider, key);
+        }
+        (Some(provider), None) => settings.set_api_provider(provider),
+        (None, None) => {}
+    }
+    if changed {
+        settings.save().map_err(|e| CliError::new(ErrorCode::ConfigError, e.to_string()))?;
+    }
+
+    Ok(Report::Config(ConfigReport {
+        game_folder: settings.get_game_folder(),
+        provider: settings.get_api_provider(),
+        api_key_set: settings.get_api_key().is_some_and(|k| !k.is_empty()),
+        keyed_providers: settings.api_keys.iter()
+            .filter(|(_, key)| !key.is_empty())
+            .map(|(provider, _)| provider.clone())
+            .collect(),
+        base_urls: settings.base_urls.clone(),
+        target_game_version: settings.get_target_game_version(),
+        max_parallel_downloads: settings.get_max_parallel_downloads(),
+        manual_download_dir: settings.get_manual_download_dir(),
+        routes: settings.routes.clone(),
+    }))
+}
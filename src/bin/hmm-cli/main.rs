@@ -0,0 +1,309 @@
+mod commands;
+mod output;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use hytale_mod_manager::api::provider::{self, registry};
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+
+#[derive(Parser)]
+#[command(name = "hmm-cli", version, about = "Headless Hytale Mod Manager")]
+pub struct Cli {
+    /// Print a versioned JSON document instead of human-readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+    /// Settings file to use instead of the one shared with the desktop app
+    #[arg(long, global = true, env = "HMM_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Override a provider's API base URL for this run, e.g. `curseforge=http://localhost:8080/v1`
+    #[arg(long, global = true, value_name = "PROVIDER=URL", value_parser = parse_provider_url)]
+    pub api_url: Vec<(ApiProvider, String)>,
+    /// Override a provider's CDN base URL for this run
+    #[arg(long, global = true, value_name = "PROVIDER=URL", value_parser = parse_provider_url)]
+    pub cdn_url: Vec<(ApiProvider, String)>,
+    /// Target this Hytale version for this run instead of the stored one
+    #[arg(long, global = true, value_name = "VERSION")]
+    pub target_version: Option<String>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Search the active provider for mods
+    Search {
+        /// Search text, leave empty to browse
+        #[arg(default_value = "")]
+        query: String,
+        #[arg(long, value_enum, default_value_t = SortArg::Featured)]
+        sort: SortArg,
+        /// 1-based result page
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+        /// Query every configured provider and merge duplicates
+        #[arg(long)]
+        all: bool,
+        /// Only mods in this category, by id, slug or name (see `categories`)
+        #[arg(long, value_name = "CATEGORY")]
+        category: Option<String>,
+        /// Only mods of this class, by id, slug or name (see `categories`)
+        #[arg(long, value_name = "CLASS")]
+        class: Option<String>,
+    },
+    /// List the classes and categories search can be filtered by
+    Categories,
+    /// Show details of a single mod
+    Info {
+        mod_id: String,
+    },
+    /// List every available file of a mod
+    Versions {
+        mod_id: String,
+    },
+    /// Download and install a mod into the game folder
+    Install {
+        mod_id: String,
+        /// Install this file id instead of the latest release
+        #[arg(long)]
+        file: Option<String>,
+        /// Install only this mod, not the mods it requires
+        #[arg(long)]
+        no_deps: bool,
+        /// Show what would be installed without downloading anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Install even if it is incompatible with an installed mod
+        #[arg(long)]
+        force: bool,
+    },
+    /// Install a jar or zip from disk, recorded under the Local provider
+    InstallFile {
+        path: PathBuf,
+        /// Content type deciding the folder (mod, asset-pack, prefab, world)
+        #[arg(long = "type", value_name = "TYPE", value_parser = parse_content_type, default_value = "mod")]
+        content_type: ContentType,
+    },
+    /// Install a mod whose author only allows downloads from the provider's website,
+    /// from the file downloaded there
+    Import {
+        mod_id: String,
+        /// Import this file id instead of the latest release
+        #[arg(long)]
+        file: Option<String>,
+        /// Folder the file was downloaded to, instead of the configured one
+        #[arg(long, value_name = "DIR")]
+        from: Option<PathBuf>,
+        /// Open the download page in the browser first
+        #[arg(long)]
+        open: bool,
+        /// Keep looking for the file for this many seconds
+        #[arg(long, value_name = "SECS")]
+        wait: Option<u64>,
+    },
+    /// Remove an installed mod by mod id or file name
+    Remove {
+        target: String,
+        /// Also remove the installed mods that depend on it
+        #[arg(long, conflicts_with = "force")]
+        with_dependents: bool,
+        /// Remove it even though other installed mods depend on it
+        #[arg(long)]
+        force: bool,
+    },
+    /// Move a mod out of the game's sight without uninstalling it
+    Disable {
+        target: String,
+    },
+    /// Put a disabled mod back into the Mods folder
+    Enable {
+        target: String,
+    },
+    /// List the mods in the game folder
+    List,
+    /// Reinstall a version an update replaced, from the local archive
+    Rollback {
+        mod_id: String,
+        /// Restore this archived file id instead of the most recent one
+        #[arg(long, conflicts_with = "list")]
+        file: Option<String>,
+        /// Only list the archived versions
+        #[arg(long)]
+        list: bool,
+    },
+    /// Check the installed mods for known incompatibilities
+    Audit,
+    /// List dependencies that no installed mod needs any more
+    Orphans {
+        /// Remove them as well
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Update outdated mods, all of them when no id is given
+    Update {
+        mod_ids: Vec<String>,
+    },
+    /// Show or change the stored configuration
+    Config {
+        #[arg(long)]
+        game_folder: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        provider: Option<ProviderArg>,
+        /// Key for `--provider`, or the active provider when omitted
+        #[arg(long)]
+        api_key: Option<String>,
+        /// Store an API base URL override, an empty URL clears it
+        #[arg(long, value_name = "PROVIDER=URL", value_parser = parse_provider_url)]
+        set_api_url: Vec<(ApiProvider, String)>,
+        /// Store a CDN base URL override, an empty URL clears it
+        #[arg(long, value_name = "PROVIDER=URL", value_parser = parse_provider_url)]
+        set_cdn_url: Vec<(ApiProvider, String)>,
+        /// Only pick files built for this Hytale version, an empty value allows any
+        #[arg(long, value_name = "VERSION")]
+        game_version: Option<String>,
+        /// How many downloads `update` runs at once, 0 restores the default
+        #[arg(long, value_name = "N")]
+        parallel_downloads: Option<usize>,
+        /// Folder `import` looks in for downloaded files, an empty value restores the system's downloads folder
+        #[arg(long, value_name = "DIR")]
+        manual_download_dir: Option<PathBuf>,
+        /// Install a content type (mod, asset-pack, prefab, world) into this folder of the game,
+        /// moving what is already installed there
+        #[arg(long, value_name = "TYPE=DIR", value_parser = parse_route)]
+        route: Vec<(ContentType, PathBuf)>,
+        /// Treat projects in this CurseForge class as a content type
+        #[arg(long, value_name = "CLASS_ID=TYPE", value_parser = parse_classification)]
+        route_class: Vec<(String, ContentType)>,
+        /// Treat projects in this Modtale category as a content type
+        #[arg(long, value_name = "CATEGORY=TYPE", value_parser = parse_classification)]
+        route_category: Vec<(String, ContentType)>,
+    },
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Search { .. } => "search",
+            Command::Categories => "categories",
+            Command::Info { .. } => "info",
+            Command::Versions { .. } => "versions",
+            Command::Install { .. } => "install",
+            Command::InstallFile { .. } => "install-file",
+            Command::Import { .. } => "import",
+            Command::Remove { .. } => "remove",
+            Command::Disable { .. } => "disable",
+            Command::Enable { .. } => "enable",
+            Command::List => "list",
+            Command::Orphans { .. } => "orphans",
+            Command::Audit => "audit",
+            Command::Rollback { .. } => "rollback",
+            Command::Update { .. } => "update",
+            Command::Config { .. } => "config",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortArg {
+    Featured,
+    Popularity,
+    Updated,
+}
+
+impl SortArg {
+    /// Matches the index of the sort dropdown on the Search page.
+    pub fn as_index(self) -> u32 {
+        match self {
+            SortArg::Featured => 0,
+            SortArg::Popularity => 1,
+            SortArg::Updated => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProviderArg {
+    #[value(name = "curseforge")]
+    CurseForge,
+    #[value(name = "modtale")]
+    Modtale,
+}
+
+impl From<ProviderArg> for ApiProvider {
+    fn from(value: ProviderArg) -> Self {
+        match value {
+            ProviderArg::CurseForge => ApiProvider::CurseForge,
+            ProviderArg::Modtale => ApiProvider::Modtale,
+        }
+    }
+}
+
+fn parse_provider_url(value: &str) -> Result<(ApiProvider, String), String> {
+    let (id, url) = value.split_once('=')
+        .ok_or_else(|| "expected PROVIDER=URL".to_string())?;
+    let provider = ApiProvider::from_id(id.trim())
+        .ok_or_else(|| format!("unknown provider `{}`", id))?;
+    Ok((provider, url.trim().to_string()))
+}
+
+fn parse_content_type(id: &str) -> Result<ContentType, String> {
+    ContentType::from_id(id).ok_or_else(|| format!(
+        "unknown content type `{}`, expected one of: {}",
+        id,
+        ContentType::ALL.map(|t| t.id()).join(", ")
+    ))
+}
+
+fn parse_route(value: &str) -> Result<(ContentType, PathBuf), String> {
+    let (id, dir) = value.split_once('=')
+        .ok_or_else(|| "expected TYPE=DIR".to_string())?;
+    let dir = dir.trim();
+    if dir.is_empty() {
+        return Err("the folder can't be empty".to_string());
+    }
+    Ok((parse_content_type(id)?, PathBuf::from(dir)))
+}
+
+fn parse_classification(value: &str) -> Result<(String, ContentType), String> {
+    let (key, id) = value.rsplit_once('=')
+        .ok_or_else(|| "expected KEY=TYPE".to_string())?;
+    Ok((key.trim().to_string(), parse_content_type(id)?))
+}
+
+/// Applies `--api-url` / `--cdn-url` on top of the stored settings without persisting them.
+fn apply_url_overrides(cli: &Cli, settings: &AppSettings) {
+    for p in registry().all() {
+        let id = p.id();
+        let mut urls = settings.effective_base_urls(&id);
+        if let Some((_, url)) = cli.api_url.iter().rev().find(|(provider, _)| *provider == id) {
+            urls.api = Some(url.clone());
+        }
+        if let Some((_, url)) = cli.cdn_url.iter().rev().find(|(provider, _)| *provider == id) {
+            urls.cdn = Some(url.clone());
+        }
+        p.set_base_urls(&urls);
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut settings = match &cli.config {
+        Some(path) => AppSettings::load_from(path.clone()),
+        None => AppSettings::load(),
+    };
+    apply_url_overrides(&cli, &settings);
+    if let Some(version) = &cli.target_version {
+        provider::set_game_version(Some(version.trim()).filter(|v| !v.is_empty()));
+    }
+
+    let command_name = cli.command.name();
+    let result = commands::run(cli.command, &mut settings).await;
+    output::emit(cli.json, command_name, &result);
+
+    match result {
+        Ok(report) if report.error().is_none() => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    }
+}
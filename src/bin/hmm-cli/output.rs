@@ -0,0 +1,549 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use hytale_mod_manager::api::archive::ArchivedVersion;
+use hytale_mod_manager::api::dependencies::{Conflict, InstallPlan, MissingDependency, SatisfiedDependency};
+use hytale_mod_manager::api::local_file::LocalModInfo;
+use hytale_mod_manager::api::local_mods::{InstallStatus, ModInstallInfo};
+use hytale_mod_manager::api::manual::download_page;
+use hytale_mod_manager::api::provider::BaseUrls;
+use hytale_mod_manager::api::routing::{ContentType, RoutingTable};
+use hytale_mod_manager::api::settings::{ApiProvider, InstalledModEntry};
+use hytale_mod_manager::api::ui_mod::{SearchFilter, UiCategory, UiMod, UiModVersion};
+
+/// Bumped whenever a field of a JSON document is renamed or removed.
+/// Adding fields is not a breaking change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NoGameFolder,
+    NotFound,
+    NotInstalled,
+    ApiError,
+    DownloadFailed,
+    InstallFailed,
+    RemoveFailed,
+    HasDependents,
+    Incompatible,
+    ManualDownloadRequired,
+    ConfigError,
+    PartialFailure,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct CliError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    schema_version: u32,
+    command: &'a str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<&'a Report>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a CliError>,
+}
+
+#[derive(Serialize)]
+pub struct SearchHit {
+    #[serde(rename = "mod")]
+    pub ui_mod: UiMod,
+    pub install: ModInstallInfo,
+}
+
+#[derive(Serialize)]
+pub struct SearchReport {
+    pub provider: ApiProvider,
+    pub federated: bool,
+    pub filter: SearchFilter,
+    pub page: u32,
+    pub total_pages: u32,
+    pub results: Vec<SearchHit>,
+}
+
+#[derive(Serialize)]
+pub struct CategoriesReport {
+    pub provider: ApiProvider,
+    pub categories: Vec<UiCategory>,
+}
+
+#[derive(Serialize)]
+pub struct InfoReport {
+    #[serde(rename = "mod")]
+    pub ui_mod: UiMod,
+    pub install: ModInstallInfo,
+}
+
+#[derive(Serialize)]
+pub struct VersionsReport {
+    pub mod_id: String,
+    pub installed_file_id: Option<String>,
+    pub target_game_version: Option<String>,
+    pub versions: Vec<UiModVersion>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallOutcome {
+    Installed,
+    Updated,
+    UpToDate,
+    Failed,
+}
+
+#[derive(Serialize)]
+pub struct InstallReport {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub provider: ApiProvider,
+    pub status: InstallOutcome,
+    pub previous_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<UiModVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CliError>,
+    /// Mods installed alongside this one because it requires them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<InstallReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub satisfied: Vec<SatisfiedDependency>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<MissingDependency>,
+    /// Incompatibilities the install went ahead despite (`--force`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Serialize)]
+pub struct InstallFileReport {
+    #[serde(flatten)]
+    pub info: LocalModInfo,
+    pub content_type: ContentType,
+}
+
+#[derive(Serialize)]
+pub struct ImportReport {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub provider: ApiProvider,
+    pub version: UiModVersion,
+    /// The downloaded file the mod was installed from.
+    pub source: PathBuf,
+}
+
+#[derive(Serialize)]
+pub struct RemoveReport {
+    pub file_name: String,
+    pub mod_id: Option<String>,
+    pub removed: bool,
+    /// Mods removed along with it because they depended on it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependents_removed: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ToggleReport {
+    pub file_name: String,
+    pub mod_id: Option<String>,
+    pub enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct ListedMod {
+    pub file_name: String,
+    pub entry: Option<InstalledModEntry>,
+}
+
+#[derive(Serialize)]
+pub struct ListReport {
+    pub mods_dir: PathBuf,
+    pub mods: Vec<ListedMod>,
+}
+
+#[derive(Serialize)]
+pub struct RollbackReport {
+    pub mod_id: String,
+    pub restored: Option<ArchivedVersion>,
+    /// What is left in the archive afterwards.
+    pub archived: Vec<ArchivedVersion>,
+}
+
+#[derive(Serialize)]
+pub struct AuditReport {
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(Serialize)]
+pub struct OrphansReport {
+    pub mods: Vec<ListedMod>,
+    pub removed: bool,
+}
+
+#[derive(Serialize)]
+pub struct UpdateReport {
+    pub results: Vec<InstallReport>,
+}
+
+#[derive(Serialize)]
+pub struct ConfigReport {
+    pub game_folder: Option<PathBuf>,
+    pub provider: ApiProvider,
+    pub api_key_set: bool,
+    pub keyed_providers: Vec<ApiProvider>,
+    pub base_urls: HashMap<ApiProvider, BaseUrls>,
+    pub target_game_version: Option<String>,
+    pub max_parallel_downloads: usize,
+    pub manual_download_dir: Option<PathBuf>,
+    pub routes: RoutingTable,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum Report {
+    Search(SearchReport),
+    Categories(CategoriesReport),
+    Info(Box<InfoReport>),
+    Versions(VersionsReport),
+    Install(Box<InstallReport>),
+    Plan(InstallPlan),
+    Import(ImportReport),
+    InstallFile(InstallFileReport),
+    Remove(RemoveReport),
+    Toggle(ToggleReport),
+    List(ListReport),
+    Orphans(OrphansReport),
+    Audit(AuditReport),
+    Rollback(RollbackReport),
+    Update(UpdateReport),
+    Config(ConfigReport),
+}
+
+fn release_label(release_type: u8) -> &'static str {
+    match release_type {
+        1 => "release",
+        2 => "beta",
+        3 => "alpha",
+        _ => "unknown",
+    }
+}
+
+fn status_label(status: &InstallStatus) -> &'static str {
+    match status {
+        InstallStatus::Installed => "installed",
+        InstallStatus::Outdated => "outdated",
+        InstallStatus::NotInstalled => "",
+    }
+}
+
+impl Report {
+    /// Error carried by a report that completed only partially.
+    pub fn error(&self) -> Option<CliError> {
+        match self {
+            Report::Install(report) if report.status == InstallOutcome::Failed => report.error.clone(),
+            Report::Install(report) => {
+                let failed = report.dependencies.iter().filter(|r| r.status == InstallOutcome::Failed).count();
+                (failed > 0).then(|| CliError::new(
+                    ErrorCode::PartialFailure,
+                    format!("{} dependency(s) failed to install", failed),
+                ))
+            }
+            Report::Update(report) => {
+                let failed = report.results.iter().filter(|r| r.status == InstallOutcome::Failed).count();
+                (failed > 0).then(|| CliError::new(
+                    ErrorCode::PartialFailure,
+                    format!("{} mod(s) failed to update", failed),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn print_human(&self) {
+        match self {
+            Report::Search(report) => {
+                if report.results.is_empty() {
+                    println!("No results found.");
+                    return;
+                }
+                for hit in &report.results {
+                    let m = &hit.ui_mod;
+                    let providers: Vec<_> = m.providers().iter().map(ApiProvider::display_name).collect();
+                    println!(
+                        "{:<10} {} by {} ({} downloads) [{}] {}{}",
+                        m.id, m.name, m.authors, m.download_count, providers.join(", "),
+                        if m.distribution_allowed { "" } else { "[manual] " },
+                        status_label(&hit.install.install_status)
+                    );
+                }
+                println!("Page {} of {}", report.page, report.total_pages);
+            }
+            Report::Categories(report) => {
+                if report.categories.is_empty() {
+                    println!("{} does not offer category filters.", report.provider.display_name());
+                }
+                let classes = report.categories.iter().filter(|c| c.is_class);
+                for class in classes {
+                    println!("{:<8} {} ({})", class.id, class.name, class.slug);
+                    for c in report.categories.iter().filter(|c| !c.is_class && c.class_id.as_ref() == Some(&class.id)) {
+                        println!("  {:<6} {} ({})", c.id, c.name, c.slug);
+                    }
+                }
+                let orphans = report.categories.iter()
+                    .filter(|c| !c.is_class && !report.categories.iter().any(|p| p.is_class && c.class_id.as_ref() == Some(&p.id)));
+                for c in orphans {
+                    println!("{:<8} {} ({})", c.id, c.name, c.slug);
+                }
+            }
+            Report::Info(report) => {
+                let m = &report.ui_mod;
+                let local_version = report.install.local_version.clone().unwrap_or_default();
+                println!("{} ({})", m.name, m.id);
+                println!("Authors:    {}", m.authors);
+                println!("Downloads:  {}", m.download_count);
+                println!("Categories: {}", m.categories.join(", "));
+                println!("Website:    {}", m.website_url);
+                println!("Latest:     {} [{}]", m.version.display_name, m.version.file_id);
+                if m.needs_manual_download(&m.version) {
+                    println!("Download:   by hand from {}", download_page(m, &m.version));
+                }
+                match report.install.install_status {
+                    InstallStatus::Installed => println!("Installed:  {}", local_version),
+                    InstallStatus::Outdated => println!("Installed:  {} (outdated)", local_version),
+                    InstallStatus::NotInstalled => println!("Installed:  no"),
+                }
+                println!();
+                println!("{}", m.summary);
+            }
+            Report::Versions(report) => {
+                if report.versions.is_empty() {
+                    println!("No files found.");
+                }
+                for v in &report.versions {
+                    let incompatible = report.target_game_version.as_deref()
+                        .is_some_and(|target| !v.supports_game_version(target));
+                    let marker = if report.installed_file_id.as_deref() == Some(v.file_id.as_str()) {
+                        "*"
+                    } else if incompatible {
+                        "!"
+                    } else {
+                        " "
+                    };
+                    println!(
+                        "{} {:<10} {:<8} {} ({}) {}",
+                        marker,
+                        v.file_id,
+                        release_label(v.release_type),
+                        v.display_name,
+                        v.upload_date,
+                        v.game_versions.join(", ")
+                    );
+                }
+            }
+            Report::Install(report) => {
+                print_install(report);
+                for dependency in &report.dependencies {
+                    print!("  ");
+                    print_install(dependency);
+                }
+                print_requirements(&report.satisfied, &report.missing);
+                print_conflicts(&report.conflicts);
+            }
+            Report::Plan(plan) => {
+                let target = plan.target();
+                println!("{} {} will be installed", target.ui_mod.name, target.version.display_name);
+                for planned in plan.dependencies() {
+                    println!(
+                        "  + {} {} (required by {})",
+                        planned.ui_mod.name,
+                        planned.version.display_name,
+                        planned.required_by.as_deref().unwrap_or_default()
+                    );
+                }
+                print_requirements(&plan.satisfied, &plan.missing);
+                print_conflicts(&plan.conflicts);
+            }
+            Report::InstallFile(report) => {
+                let info = &report.info;
+                println!("Installed {} {} from {} ({})", info.name, info.version, info.file_name, info.mod_id);
+                if !info.dependencies.is_empty() {
+                    println!("  requires {}", info.dependencies.join(", "));
+                }
+            }
+            Report::Import(report) => {
+                println!(
+                    "Imported {} {} from {}",
+                    report.mod_name,
+                    report.version.display_name,
+                    report.source.display()
+                );
+            }
+            Report::Rollback(report) => {
+                match &report.restored {
+                    Some(restored) => println!("Rolled back to {} ({})", restored.entry.version_name, restored.file_name),
+                    None if report.archived.is_empty() => println!("No archived versions of {}.", report.mod_id),
+                    None => {}
+                }
+                if report.restored.is_none() {
+                    for archived in &report.archived {
+                        println!("{:<10} {} ({})", archived.entry.file_id, archived.entry.version_name, archived.file_name);
+                    }
+                }
+            }
+            Report::Audit(report) => {
+                if report.conflicts.is_empty() {
+                    println!("No known incompatibilities.");
+                }
+                for c in &report.conflicts {
+                    println!("{} is incompatible with {}", c.mod_name, c.other_mod_name);
+                }
+            }
+            Report::Remove(report) => {
+                for dependent in &report.dependents_removed {
+                    println!("Removed {} (depended on {})", dependent, report.file_name);
+                }
+                println!("Removed {}", report.file_name);
+            }
+            Report::Toggle(report) => {
+                println!("{} {}", if report.enabled { "Enabled" } else { "Disabled" }, report.file_name);
+            }
+            Report::Orphans(report) => {
+                if report.mods.is_empty() {
+                    println!("No unused dependencies.");
+                }
+                for listed in &report.mods {
+                    let name = listed.entry.as_ref().map_or(listed.file_name.as_str(), |e| e.mod_name.as_str());
+                    if report.removed {
+                        println!("Removed {} ({})", name, listed.file_name);
+                    } else {
+                        println!("{} ({})", name, listed.file_name);
+                    }
+                }
+            }
+            Report::List(report) => {
+                if report.mods.is_empty() {
+                    println!("No mods found in {}", report.mods_dir.display());
+                }
+                for listed in &report.mods {
+                    match &listed.entry {
+                        Some(entry) => println!(
+                            "{:<10} {} {} [{}] {}{}",
+                            entry.mod_id,
+                            entry.mod_name,
+                            entry.version_name,
+                            entry.api_provider.display_name(),
+                            listed.file_name,
+                            if entry.disabled { " (disabled)" } else { "" }
+                        ),
+                        None => println!("{:<10} (untracked) {}", "-", listed.file_name),
+                    }
+                }
+            }
+            Report::Update(report) => {
+                if report.results.is_empty() {
+                    println!("Nothing to update.");
+                }
+                for result in &report.results {
+                    print_install(result);
+                }
+            }
+            Report::Config(report) => {
+                let folder = report.game_folder.as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "(not set)".to_string());
+                println!("Game folder: {}", folder);
+                println!("Provider:    {}", report.provider.display_name());
+                println!("API key:     {}", if report.api_key_set { "set" } else { "not set" });
+                println!("Game version: {}", report.target_game_version.as_deref().unwrap_or("any"));
+                println!("Downloads:   {} at a time", report.max_parallel_downloads);
+                let manual_dir = report.manual_download_dir.as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "(not set)".to_string());
+                println!("Import from: {}", manual_dir);
+                for content_type in ContentType::ALL {
+                    println!("{:<12} {}", format!("{}:", content_type.display_name()), report.routes.folder(content_type).display());
+                }
+                let keyed: Vec<_> = report.keyed_providers.iter().map(ApiProvider::display_name).collect();
+                println!("Keys stored: {}", if keyed.is_empty() { "none".to_string() } else { keyed.join(", ") });
+                for (api_provider, urls) in &report.base_urls {
+                    if let Some(api) = &urls.api {
+                        println!("{} API URL: {}", api_provider.display_name(), api);
+                    }
+                    if let Some(cdn) = &urls.cdn {
+                        println!("{} CDN URL: {}", api_provider.display_name(), cdn);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn print_install(report: &InstallReport) {
+    let previous = report.previous_version.clone().unwrap_or_default();
+    let version = report.version.as_ref().map(|v| v.display_name.clone()).unwrap_or_default();
+
+    match report.status {
+        InstallOutcome::Installed => println!("{}: installed {}", report.mod_name, version),
+        InstallOutcome::Updated => println!("{}: {} -> {}", report.mod_name, previous, version),
+        InstallOutcome::UpToDate => println!("{}: up to date ({})", report.mod_name, previous),
+        InstallOutcome::Failed => {
+            let message = report.error.as_ref().map(|e| e.message.as_str()).unwrap_or_default();
+            eprintln!("{}: failed: {}", report.mod_name, message);
+        }
+    }
+}
+
+fn print_requirements(satisfied: &[SatisfiedDependency], missing: &[MissingDependency]) {
+    for s in satisfied {
+        println!("  = {} {} already installed (required by {})", s.mod_name, s.version_name, s.required_by);
+    }
+    for m in missing {
+        eprintln!("  ! {} can't be installed (required by {}): {}", m.mod_id, m.required_by, m.reason);
+    }
+}
+
+fn print_conflicts(conflicts: &[Conflict]) {
+    for c in conflicts {
+        eprintln!("  ! {} is incompatible with {}", c.mod_name, c.other_mod_name);
+    }
+}
+
+/// Writes the outcome of a command to stdout, either as human text or as one JSON document.
+pub fn emit(json: bool, command: &str, result: &Result<Report, CliError>) {
+    let error = match result {
+        Ok(report) => report.error(),
+        Err(e) => Some(e.clone()),
+    };
+
+    if json {
+        let envelope = Envelope {
+            schema_version: SCHEMA_VERSION,
+            command,
+            ok: error.is_none(),
+            data: result.as_ref().ok(),
+            error: error.as_ref(),
+        };
+        match serde_json::to_string_pretty(&envelope) {
+            Ok(text) => println!("{}", text),
+            Err(e) => eprintln!("Failed to serialize output: {}", e),
+        }
+        return;
+    }
+
+    if let Ok(report) = result {
+        report.print_human();
+    }
+    if let Some(e) = error {
+        eprintln!("Error: {}", e.message);
+    }
+}
@@ -1,4 +1,7 @@
 use dioxus::prelude::*;
+use std::path::PathBuf;
+use crate::api::local_mods::set_route;
+use crate::api::routing::ContentType;
 use crate::api::settings::{AppSettings, ApiProvider};
 use crate::components::drop_down::ThemeDropdown;
 
@@ -8,12 +11,39 @@ pub fn ApiDialog(on_close: EventHandler<()>) -> Element {
 
     let current_settings = app_settings.read();
     let mut selected_provider = use_signal(|| current_settings.api_provider.clone());
-    let mut input_val = use_signal(|| current_settings.api_key.clone().unwrap_or_default());
+    let mut input_val = use_signal(|| current_settings.get_api_key().unwrap_or_default());
+    let mut game_version_val = use_signal(|| current_settings.get_target_game_version().unwrap_or_default());
+    let mut parallel_val = use_signal(|| current_settings.get_max_parallel_downloads().to_string());
+    let mut route_vals = use_signal(|| ContentType::ALL.map(|t| current_settings.routes.folder(t).display().to_string()));
+    let mut error_msg = use_signal(|| Option::<String>::None);
 
     let handle_save = move |_| {
         let key = input_val();
         let provider = selected_provider();
         app_settings.write().change_api(provider, key);
+        app_settings.write().set_target_game_version(Some(game_version_val()));
+        app_settings.write().set_max_parallel_downloads(parallel_val().trim().parse().ok());
+
+        // Changing a folder moves what is already installed there.
+        let game_folder = app_settings.read().get_game_folder();
+        for (content_type, value) in ContentType::ALL.into_iter().zip(route_vals()) {
+            let value = PathBuf::from(value.trim());
+            if value.as_os_str().is_empty() || value == app_settings.read().routes.folder(content_type) {
+                continue;
+            }
+            let result = match &game_folder {
+                Some(folder) => set_route(folder, content_type, value, &mut app_settings.write()),
+                None => {
+                    app_settings.write().routes.folders.insert(content_type, value);
+                    Ok(())
+                }
+            };
+            if let Err(e) = result {
+                error_msg.set(Some(e));
+                return;
+            }
+        }
+
         let _ = app_settings.read().save();
         on_close.call(());
     };
@@ -37,11 +67,13 @@ pub fn ApiDialog(on_close: EventHandler<()>) -> Element {
                         placeholder: "Api Provider",
                         default_index: Some(selected_provider().clone() as usize),
                         on_select: move |idx| {
-                            match idx {
-                                0 => selected_provider.set(ApiProvider::Modtale),
-                                1 => selected_provider.set(ApiProvider::CurseForge),
-                                _ => {}
-                            }
+                            let provider = match idx {
+                                0 => ApiProvider::Modtale,
+                                1 => ApiProvider::CurseForge,
+                                _ => return,
+                            };
+                            input_val.set(app_settings.read().get_api_key_for(&provider).unwrap_or_default());
+                            selected_provider.set(provider);
                         },
                     }
                 }
@@ -64,6 +96,51 @@ pub fn ApiDialog(on_close: EventHandler<()>) -> Element {
                     }
                 }
 
+                div { style: "display: flex; flex-direction: column; gap: 5px;",
+                    label { style: "font-size: 12px; color: var(--text-secondary);", "Target Hytale Version" }
+                    input {
+                        style: "padding: 8px; border-radius: 5px; background: var(--bg-secondary); color: var(--text-primary); border: 1px solid var(--border-color);",
+                        placeholder: "Any version",
+                        value: "{game_version_val}",
+                        oninput: move |e| game_version_val.set(e.value())
+                    }
+                    div { style: "font-size: 10px; color: var(--text-secondary); margin-top: 2px;",
+                        "Searches and updates only pick files built for this version."
+                    }
+                }
+
+                div { style: "display: flex; flex-direction: column; gap: 5px;",
+                    label { style: "font-size: 12px; color: var(--text-secondary);", "Parallel Downloads" }
+                    input {
+                        style: "padding: 8px; border-radius: 5px; background: var(--bg-secondary); color: var(--text-primary); border: 1px solid var(--border-color);",
+                        r#type: "number",
+                        min: "1",
+                        value: "{parallel_val}",
+                        oninput: move |e| parallel_val.set(e.value())
+                    }
+                }
+
+                div { style: "display: flex; flex-direction: column; gap: 5px;",
+                    label { style: "font-size: 12px; color: var(--text-secondary);", "Install Folders" }
+                    for (index, content_type) in ContentType::ALL.into_iter().enumerate() {
+                        div { key: "{content_type.id()}", style: "display: flex; align-items: center; gap: 8px;",
+                            span { style: "width: 80px; font-size: 12px; color: var(--text-secondary);", "{content_type.display_name()}" }
+                            input {
+                                style: "flex: 1; padding: 6px; border-radius: 5px; background: var(--bg-secondary); color: var(--text-primary); border: 1px solid var(--border-color);",
+                                value: "{route_vals.read()[index]}",
+                                oninput: move |e| route_vals.write()[index] = e.value()
+                            }
+                        }
+                    }
+                    div { style: "font-size: 10px; color: var(--text-secondary); margin-top: 2px;",
+                        "Relative to the game folder. Installed files move along when a folder changes."
+                    }
+                }
+
+                if let Some(err) = error_msg() {
+                    span { style: "font-size: 12px; color: var(--danger);", "{err}" }
+                }
+
                 div { style: "display: flex; gap: 10px; margin-top: 10px;",
                     button {
                         class: "btn btn-ghost",
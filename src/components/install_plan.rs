@@ -0,0 +1,83 @@
+use dioxus::prelude::*;
+use crate::api::dependencies::InstallPlan;
+use crate::api::settings::AppSettings;
+use crate::state::mod_store::start_plan;
+
+#[component]
+pub fn InstallPlanDialog(plan: InstallPlan, on_close: EventHandler<()>) -> Element {
+    let app_settings = use_context::<Signal<AppSettings>>();
+    let target = plan.target().clone();
+
+    let confirm_plan = plan.clone();
+    let handle_confirm = move |_| {
+        start_plan(app_settings, &confirm_plan, |_| {});
+        on_close.call(());
+    };
+
+    rsx! {
+        div {
+            style: "position: fixed; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.7); display: flex; align-items: center; justify-content: center; z-index: 100;",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                style: "background-color: var(--bg-tertiary); width: 460px; max-height: 80vh; padding: 25px; border-radius: 10px; display: flex; flex-direction: column; gap: 15px; border: 1px solid var(--border-color); box-shadow: 0 4px 15px rgba(0,0,0,0.5);",
+                onclick: |e| e.stop_propagation(),
+
+                h3 { style: "margin: 0; color: var(--text-primary);",
+                    "Install {target.ui_mod.name} {target.version.display_name}"
+                }
+
+                div { style: "flex: 1; overflow-y: auto; display: flex; flex-direction: column; gap: 6px; font-size: 13px;",
+                    if !plan.dependencies().is_empty() {
+                        span { style: "font-size: 12px; color: var(--text-secondary);", "Also installs" }
+                    }
+                    for planned in plan.dependencies().iter().cloned() {
+                        div { key: "{planned.ui_mod.id}", style: "color: var(--text-primary);",
+                            "{planned.ui_mod.name} {planned.version.display_name}"
+                            span { style: "color: var(--text-secondary);",
+                                " (required by {planned.required_by.clone().unwrap_or_default()})"
+                            }
+                        }
+                    }
+                    if !plan.satisfied.is_empty() {
+                        span { style: "font-size: 12px; color: var(--text-secondary); margin-top: 6px;", "Already installed" }
+                    }
+                    for satisfied in plan.satisfied.iter().cloned() {
+                        div { key: "{satisfied.mod_id}", style: "color: var(--success);",
+                            "{satisfied.mod_name} {satisfied.version_name}"
+                        }
+                    }
+                    if !plan.missing.is_empty() {
+                        span { style: "font-size: 12px; color: var(--text-secondary); margin-top: 6px;", "Can't be installed" }
+                    }
+                    for missing in plan.missing.iter().cloned() {
+                        div { key: "{missing.mod_id}", style: "color: var(--danger);",
+                            "{missing.mod_id}: {missing.reason} (required by {missing.required_by})"
+                        }
+                    }
+                    if !plan.conflicts.is_empty() {
+                        span { style: "font-size: 12px; color: var(--text-secondary); margin-top: 6px;", "Known incompatibilities" }
+                    }
+                    for conflict in plan.conflicts.iter().cloned() {
+                        div { key: "{conflict.mod_id}-{conflict.other_mod_id}", style: "color: var(--warning);",
+                            "⚠ {conflict.mod_name} is incompatible with {conflict.other_mod_name}"
+                        }
+                    }
+                }
+
+                div { style: "display: flex; gap: 10px; justify-content: flex-end;",
+                    button {
+                        class: "btn btn-neutral",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+                    button {
+                        class: "btn btn-brand",
+                        onclick: handle_confirm,
+                        "Install {plan.installs.len()} mod(s)"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use dioxus::prelude::*;
+use crate::api::manual::{download_page, import_download, open_url, wait_for_download};
+use crate::api::settings::AppSettings;
+use crate::state::mod_store::PendingManual;
+
+/// Walks the user through downloading a mod from the provider's website and
+/// installs the file as soon as it shows up in the watched folder.
+#[component]
+pub fn ManualDownloadDialog(pending: PendingManual, on_close: EventHandler<()>) -> Element {
+    let mut app_settings = use_context::<Signal<AppSettings>>();
+    let mut error_msg = use_signal(|| Option::<String>::None);
+    let watch_dir = use_memo(move || app_settings.read().get_manual_download_dir());
+
+    let page = download_page(&pending.ui_mod, &pending.version);
+    let provider_name = pending.ui_mod.provider.display_name();
+
+    // Restarts whenever the watched folder changes, and stops with the dialog.
+    let watched = pending.clone();
+    use_resource(move || {
+        let watched = watched.clone();
+        async move {
+            let Some(dir) = watch_dir() else { return };
+            let Some(path) = wait_for_download(&dir, &watched.version, None).await else { return };
+            let Some(folder) = app_settings.peek().get_game_folder() else {
+                error_msg.set(Some("No Game Folder Set".to_string()));
+                return;
+            };
+            match import_download(&folder, &path, &watched.ui_mod, &watched.version, &mut app_settings.write()) {
+                Ok(()) => on_close.call(()),
+                Err(e) => error_msg.set(Some(e)),
+            }
+        }
+    });
+
+    let open_page = {
+        let page = page.clone();
+        move |_| {
+            if let Err(e) = open_url(&page) {
+                error_msg.set(Some(format!("Failed to open the browser: {}", e)));
+            }
+        }
+    };
+
+    let pick_folder = move |_| {
+        spawn(async move {
+            if let Some(path) = rfd::AsyncFileDialog::new().pick_folder().await {
+                app_settings.write().set_manual_download_dir(Some(path.path().to_owned()));
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            style: "position: fixed; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.7); display: flex; align-items: center; justify-content: center; z-index: 100;",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                style: "background-color: var(--bg-tertiary); width: 460px; padding: 25px; border-radius: 10px; display: flex; flex-direction: column; gap: 15px; border: 1px solid var(--border-color); box-shadow: 0 4px 15px rgba(0,0,0,0.5);",
+                onclick: |e| e.stop_propagation(),
+
+                h3 { style: "margin: 0; color: var(--text-primary);",
+                    "Download {pending.ui_mod.name} {pending.version.display_name}"
+                }
+                span { style: "font-size: 13px; color: var(--text-secondary);",
+                    "The author only allows downloads from {provider_name}'s website. Download "
+                    b { "{pending.version.file_name}" }
+                    " there and it will be installed from the folder below."
+                }
+                div { style: "font-size: 12px; color: var(--text-secondary); word-break: break-all;", "{page}" }
+
+                div { style: "display: flex; align-items: center; gap: 10px; font-size: 12px; color: var(--text-secondary);",
+                    if let Some(dir) = watch_dir() {
+                        div { class: "spinner" }
+                        span { style: "flex: 1; word-break: break-all;", "Watching {dir.display()}" }
+                    } else {
+                        span { style: "flex: 1; color: var(--danger);", "No downloads folder found" }
+                    }
+                    button {
+                        class: "btn btn-ghost",
+                        onclick: pick_folder,
+                        "Change"
+                    }
+                }
+                if let Some(err) = error_msg() {
+                    span { style: "font-size: 12px; color: var(--danger);", "{err}" }
+                }
+
+                div { style: "display: flex; gap: 10px; justify-content: flex-end;",
+                    button {
+                        class: "btn btn-neutral",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+                    button {
+                        class: "btn btn-brand",
+                        onclick: open_page,
+                        "Open download page"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,4 +2,7 @@ pub mod api_dialog;
 pub mod mod_info;
 pub mod sidebar;
 pub mod drop_down;
-pub mod mod_card;
\ No newline at end of file
+pub mod mod_card;
+pub mod install_plan;
+pub mod remove_dialog;
+pub mod manual_download;
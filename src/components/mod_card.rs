@@ -1,10 +1,12 @@
 use dioxus::prelude::*;
 use dioxus::events::MouseData;
-use crate::api::ui_mod::download_version_unified;
-use crate::api::local_mods::{InstallStatus, ModInstallInfo, check_install_status, install_mod, remove_mod};
-use crate::api::settings::{AppSettings, ApiProvider};
+use crate::api::archive::archived_versions;
+use crate::api::download::{format_bytes, DownloadProgress};
+use crate::api::local_mods::{InstallStatus, ModInstallInfo, check_install_status, restore_archived, set_mod_enabled};
+use crate::api::queue::{Job, JobState};
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
 use crate::api::ui_mod::UiMod;
-use crate::state::mod_store::ModStore;
+use crate::state::mod_store::{request_install, request_remove, ModStore};
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum ButtonAction {
@@ -22,10 +24,21 @@ pub struct ButtonState {
     pub action: ButtonAction,
 }
 
+/// Button text while the queue has a job for the mod.
+pub fn busy_text(job: Option<&Job>) -> &'static str {
+    match job.map(|j| &j.state) {
+        Some(JobState::Queued) => "QUEUED",
+        Some(JobState::Downloading { .. }) => "DOWNLOADING...",
+        Some(JobState::Verifying) => "VERIFYING...",
+        Some(JobState::Installing) => "INSTALLING...",
+        _ => "WORKING...",
+    }
+}
+
 #[component]
 pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) -> Element {
-    let mut app_settings = use_context::<Signal<AppSettings>>();
-    let mut mod_store = use_context::<Signal<ModStore>>();
+    let app_settings = use_context::<Signal<AppSettings>>();
+    let mod_store = use_context::<Signal<ModStore>>();
 
     let install_info = use_memo(move || {
         if mod_data().id == "0" {
@@ -58,7 +71,7 @@ pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) ->
 
         if is_processing {
             ButtonState {
-                text: "WORKING...",
+                text: busy_text(store.active_job(&mod_data().id).as_ref()),
                 class: "btn-secondary",
                 disabled: true,
                 action: ButtonAction::None
@@ -91,6 +104,9 @@ pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) ->
     });
 
     let has_icon = !mod_data().icon.is_empty();
+    let is_disabled = app_settings.read().installed_mods.values()
+        .any(|e| e.mod_id == mod_data().id && e.disabled);
+    let card_opacity = if is_disabled { "0.6" } else { "1" };
 
     let handle_action = move |e: Event<MouseData>| {
         e.stop_propagation();
@@ -107,87 +123,38 @@ pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) ->
         let current_action = button_info().action;
         if let ButtonAction::None = current_action { return }
 
-        let mod_id = mod_data().id.clone();
-        let file_id = mod_data().version.file_id.clone();
-        let file_name = mod_data().version.file_name.clone();
-        let mod_name = mod_data().name.clone();
-        let version_name = mod_data().version.display_name.clone();
-        let version_data = mod_data().version.clone();
         let local_file_to_remove = install_info().local_file_name.clone();
-        let provider = app_settings.read().api_provider.clone();
 
-        let mut error_msg_clone = error_msg.clone();
-        let mut settings_signal = app_settings.clone();
-
-        mod_store.write().set_processing(&mod_id, true);
         error_msg.set(None);
 
-        spawn(async move {
-            match current_action {
-                ButtonAction::Install | ButtonAction::Update => {
-                    if current_action == ButtonAction::Update {
-                        if let Some(old_file) = local_file_to_remove {
-                            let mut settings = settings_signal.write();
-                            match remove_mod(&folder, &old_file, &mut settings) {
-                                Ok(_) => {},
-                                Err(_) => error_msg_clone.set(Some("FAILED to remove old file".to_string())),
-                            }
-                        }
+        match current_action {
+            ButtonAction::Install | ButtonAction::Update => {
+                // The queue swaps out the old file only once the new one is
+                // downloaded and verified.
+                request_install(mod_store(), app_settings, mod_data(), mod_data().version.clone(), matches!(current_action, ButtonAction::Update), move |state| {
+                    if let JobState::Failed { error, .. } = state {
+                        error_msg.set(Some(format!("Download failed: {}", error)));
                     }
-
-                    let download_res = {
-                        let settings = settings_signal.read();
-                        download_version_unified(&settings, &version_data).await
-                    };
-
-                    match download_res {
-                        Ok((_, bytes)) => {
-                            let mut settings = settings_signal.write();
-
-                            match install_mod(
-                                &folder,
-                                &file_name,
-                                &bytes,
-                                mod_id.clone(),
-                                mod_name.clone(),
-                                file_id,
-                                version_name.clone(),
-                                provider,
-                                &mut settings
-                            ) {
-                                Ok(_) => {},
-                                Err(e) => {
-                                    error_msg_clone.set(Some(format!("Install error: {}", e)));
-                                }
-                            }
-                        }
+                });
+            }
+            ButtonAction::Remove => {
+                if let Some(local_name) = local_file_to_remove {
+                    match request_remove(mod_store(), app_settings, &folder, &local_name) {
+                        Ok(_) => {},
                         Err(e) => {
-                            error_msg_clone.set(Some(format!("Download failed: {}", e)));
-                        }
-                    }
-                }
-                ButtonAction::Remove => {
-                    if let Some(local_name) = local_file_to_remove {
-                        let mut settings = settings_signal.write();
-                        match remove_mod(&folder, &local_name, &mut settings) {
-                            Ok(_) => {},
-                            Err(e) => {
-                                error_msg_clone.set(Some(e));
-                            }
+                            error_msg.set(Some(e));
                         }
                     }
                 }
-                _ => {},
             }
-
-            mod_store.write().set_processing(&mod_id, false);
-        });
+            _ => {},
+        }
     };
 
     rsx! {
         div {
             onclick: move |_| onclick.call(mod_data()),
-            style: "cursor: pointer; background-color: var(--bg-tertiary); padding: 10px; border-radius: 8px; display: flex; align-items: center; gap: 15px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); transition: transform 0.1s;",
+            style: "cursor: pointer; background-color: var(--bg-tertiary); padding: 10px; border-radius: 8px; display: flex; align-items: center; gap: 15px; box-shadow: 0 2px 4px rgba(0,0,0,0.1); transition: transform 0.1s; opacity: {card_opacity};",
 
             div {
                 style: "width: 50px; height: 50px; background-color: var(--bg-secondary); border-radius: 4px; overflow: hidden; display: flex; align-items: center; justify-content: center;",
@@ -231,14 +198,45 @@ pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) ->
                         }
                     }
                 }
-                div { style: "font-size: 11px; color: var(--brand-primary); margin-bottom: 2px;", "By {mod_data().authors}" }
+                div { style: "display: flex; align-items: center; gap: 6px; margin-bottom: 2px;",
+                    span { style: "font-size: 11px; color: var(--brand-primary);", "By {mod_data().authors}" }
+                    if !mod_data().distribution_allowed {
+                        span {
+                            style: "font-size: 9px; background: var(--bg-secondary); color: var(--warning); padding: 1px 5px; border-radius: 4px; border: 1px solid var(--warning);",
+                            title: "Downloaded by hand from the provider's website",
+                            "MANUAL"
+                        }
+                    }
+                    if !mod_data().other_sources.is_empty() || app_settings.read().is_federated_search() {
+                        for provider in mod_data().providers() {
+                            span {
+                                style: "font-size: 9px; background: var(--bg-secondary); color: var(--text-secondary); padding: 1px 5px; border-radius: 4px; border: 1px solid var(--border-color);",
+                                "{provider.display_name()}"
+                            }
+                        }
+                    }
+                }
                 if let Some(err) = error_msg() {
                     div { style: "font-size: 10px; color: var(--danger);", "{err}" }
+                } else if let Some(progress) = mod_store.read().progress(&mod_data().id) {
+                    DownloadProgressBar { progress }
                 } else {
                     div { style: "font-size: 12px; color: var(--text-secondary);", "{mod_data().summary}" }
                 }
             }
 
+            if install_info().install_status != InstallStatus::NotInstalled && !button_info().disabled {
+                EnableToggle {
+                    provider: mod_data().provider.clone(),
+                    mod_id: mod_data().id.clone(),
+                    on_error: move |e| error_msg.set(Some(e)),
+                }
+                RollbackButton {
+                    provider: mod_data().provider.clone(),
+                    mod_id: mod_data().id.clone(),
+                    on_error: move |e| error_msg.set(Some(e)),
+                }
+            }
             button {
                 class: "btn {button_info().class}",
                 disabled: button_info().disabled,
@@ -247,4 +245,96 @@ pub fn ModCard(mod_data: ReadOnlySignal<UiMod>, onclick: EventHandler<UiMod>) ->
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Switches an installed mod on or off by moving it between the Mods folder
+/// and the holding folder. Renders nothing for files the manifest doesn't know.
+/// The manifest entry of `mod_id` from `provider`, with its file name. As a
+/// memo, it only notifies when that entry changes, not on every settings write.
+fn use_installed_entry(
+    provider: ReadOnlySignal<ApiProvider>,
+    mod_id: ReadOnlySignal<String>,
+) -> Memo<Option<(String, InstalledModEntry)>> {
+    let app_settings = use_context::<Signal<AppSettings>>();
+    use_memo(move || {
+        app_settings.read().installed_mods.iter()
+            .find(|(_, e)| e.api_provider == provider() && e.mod_id == mod_id())
+            .map(|(file_name, e)| (file_name.clone(), e.clone()))
+    })
+}
+
+#[component]
+pub fn EnableToggle(provider: ReadOnlySignal<ApiProvider>, mod_id: ReadOnlySignal<String>, on_error: EventHandler<String>) -> Element {
+    let mut app_settings = use_context::<Signal<AppSettings>>();
+    let installed = use_installed_entry(provider, mod_id);
+
+    let Some((file_name, entry)) = installed() else { return rsx! {} };
+    let enabled = !entry.disabled;
+
+    rsx! {
+        button {
+            class: if enabled { "btn btn-neutral" } else { "btn btn-ghost" },
+            title: if enabled { "Disable without uninstalling" } else { "Move back into the Mods folder" },
+            onclick: move |e: Event<MouseData>| {
+                e.stop_propagation();
+                let Some(folder) = app_settings.read().get_game_folder() else { return };
+                if let Err(err) = set_mod_enabled(&folder, &file_name, !enabled, &mut app_settings.write()) {
+                    on_error.call(err);
+                }
+            },
+            if enabled { "ON" } else { "OFF" }
+        }
+    }
+}
+
+/// Puts back the version an update replaced, from the local archive. Renders
+/// nothing when the installed mod has no archived versions.
+#[component]
+pub fn RollbackButton(provider: ReadOnlySignal<ApiProvider>, mod_id: ReadOnlySignal<String>, on_error: EventHandler<String>) -> Element {
+    let mut app_settings = use_context::<Signal<AppSettings>>();
+    let installed = use_installed_entry(provider, mod_id);
+
+    // Reading the archive lists folders and parses their entries, so it only
+    // happens when the installed entry changes, which every update and
+    // rollback does.
+    let previous = use_memo(move || {
+        let (_, entry) = installed()?;
+        archived_versions(&app_settings.peek(), &entry.api_provider, &entry.mod_id).into_iter().next()
+    });
+    let Some(previous) = previous() else { return rsx! {} };
+    let version_name = previous.entry.version_name.clone();
+
+    rsx! {
+        button {
+            class: "btn btn-neutral",
+            title: "Roll back to {version_name} without downloading it again",
+            onclick: move |e: Event<MouseData>| {
+                e.stop_propagation();
+                let Some(folder) = app_settings.read().get_game_folder() else { return };
+                if let Err(err) = restore_archived(&folder, &previous, &mut app_settings.write()) {
+                    on_error.call(err);
+                }
+            },
+            "↺ {version_name}"
+        }
+    }
+}
+
+#[component]
+pub fn DownloadProgressBar(progress: DownloadProgress) -> Element {
+    // Without a known size the bar just fills, with the byte count beside it.
+    let width = format!("{:.0}%", progress.fraction().unwrap_or(1.0) * 100.0);
+    let label = match progress.total {
+        Some(total) => format!("{} / {}", format_bytes(progress.received), format_bytes(total)),
+        None => format_bytes(progress.received),
+    };
+
+    rsx! {
+        div { style: "display: flex; align-items: center; gap: 8px; margin-top: 4px;",
+            div { style: "flex: 1; height: 6px; background-color: var(--bg-secondary); border-radius: 3px; overflow: hidden;",
+                div { style: "height: 100%; width: {width}; background-color: var(--brand-primary); transition: width 0.1s;" }
+            }
+            span { style: "font-size: 10px; color: var(--text-secondary); white-space: nowrap;", "{label}" }
+        }
+    }
+}
@@ -1,28 +1,60 @@
-use crate::api::ui_mod::{download_version_unified, get_mod_versions_unified};
-use crate::api::local_mods::{check_install_status, install_mod, remove_mod, InstallStatus};
+use crate::api::ui_mod::get_mod_versions_page_unified;
+use crate::api::local_mods::{check_install_status, InstallStatus};
+use crate::api::queue::JobState;
 use crate::api::settings::AppSettings;
 use crate::api::ui_mod::{UiMod, UiModVersion};
-use crate::components::mod_card::{ButtonAction, ButtonState};
-use crate::state::mod_store::ModStore;
+use crate::components::mod_card::{busy_text, ButtonAction, ButtonState, DownloadProgressBar, RollbackButton};
+use crate::state::mod_store::{request_install, request_remove, ModStore};
 use dioxus::events::MouseData;
 use dioxus::prelude::*;
 
 #[component]
 pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
     let mod_data = use_signal(|| mod_data.clone());
-    let mut app_settings = use_context::<Signal<AppSettings>>();
-    let mut mod_store = use_context::<Signal<ModStore>>();
+    let app_settings = use_context::<Signal<AppSettings>>();
+    let mod_store = use_context::<Signal<ModStore>>();
 
     let mut active_tab = use_signal(|| "overview");
     let mut displayed_versions = use_signal(|| vec![]);
+    let mut versions_total = use_signal(|| Option::<u32>::None);
+    let mut has_older_versions = use_signal(|| false);
+    let mut loading_versions = use_signal(|| false);
 
     use_resource(use_reactive(&mod_data().id, move |id| async move {
-        let settings = app_settings.read();
-        if let Ok(versions) = get_mod_versions_unified(&settings, &id).await {
-            displayed_versions.set(versions);
+        let provider = mod_data.peek().provider.clone();
+        if let Ok(page) = get_mod_versions_page_unified(&provider, &id, 0).await {
+            displayed_versions.set(page.versions);
+            versions_total.set(page.total_count);
+            has_older_versions.set(page.has_more);
         }
     }));
 
+    let load_older_versions = move |_| {
+        let provider = mod_data.peek().provider.clone();
+        let id = mod_data.peek().id.clone();
+        let offset = displayed_versions.peek().len() as u32;
+        loading_versions.set(true);
+
+        spawn(async move {
+            match get_mod_versions_page_unified(&provider, &id, offset).await {
+                Ok(page) => {
+                    displayed_versions.write().extend(page.versions);
+                    versions_total.set(page.total_count);
+                    has_older_versions.set(page.has_more);
+                }
+                Err(e) => eprintln!("Failed to load older versions: {}", e),
+            }
+            loading_versions.set(false);
+        });
+    };
+
+    let versions_label = match versions_total() {
+        Some(total) if total as usize > displayed_versions.read().len() => {
+            format!("Versions ({} of {})", displayed_versions.read().len(), total)
+        }
+        _ => format!("Versions ({})", displayed_versions.read().len()),
+    };
+
     let is_processing = mod_store().is_processing(&mod_data().id);
 
     let install_info = use_memo(move || {
@@ -47,7 +79,7 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
 
         if is_processing {
             ButtonState {
-                text: "WORKING...",
+                text: busy_text(store.active_job(&mod_data().id).as_ref()),
                 class: "btn-secondary",
                 disabled: true,
                 action: ButtonAction::None
@@ -80,7 +112,6 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
     });
 
     let mod_id_for_versions = mod_data().id.clone();
-    let mod_name_for_versions = mod_data().name.clone();
 
     let handle_action = move |e: Event<MouseData>| {
         e.stop_propagation();
@@ -95,79 +126,35 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
         };
 
         let current_action = button_info().action;
-        let mod_id = mod_data().id.clone();
-        let mod_name = mod_data().name.clone();
-
-        let version_data = mod_data().version.clone();
-        let file_id = version_data.file_id.clone();
-        let file_name = version_data.file_name.clone();
-        let version_name = version_data.display_name.clone();
-        let download_url_str = version_data.download_url.clone();
-
         let local_file_to_remove = install_info().local_file_name.clone();
-        let provider = app_settings.read().api_provider.clone();
-
-        let mut error_msg_clone = error_msg.clone();
-        let mut settings_signal = app_settings.clone();
 
-        mod_store.write().set_processing(&mod_id, true);
         error_msg.set(None);
 
-        spawn(async move {
-            match current_action {
-                ButtonAction::Install | ButtonAction::Update => {
-                    if current_action == ButtonAction::Update {
-                        if let Some(old_file) = local_file_to_remove {
-                            let mut settings = settings_signal.write();
-                            let _ = remove_mod(&folder, &old_file, &mut settings);
-                        }
-                    }
+        match current_action {
+            ButtonAction::Install | ButtonAction::Update => {
+                if mod_data().version.download_url.is_none() && !mod_data().needs_manual_download(&mod_data().version) {
+                    error_msg.set(Some("No download URL".to_string()));
+                    return;
+                }
 
-                    if let Some(url) = download_url_str {
-                        let download_res = {
-                            let settings = settings_signal.read();
-                            download_version_unified(&settings, &version_data).await
-                        };
-
-                        match download_res {
-                            Ok((_, bytes)) => {
-                                let mut settings = settings_signal.write();
-                                match install_mod(
-                                    &folder,
-                                    &file_name,
-                                    &bytes,
-                                    mod_id.clone(),
-                                    mod_name,
-                                    file_id,
-                                    version_name,
-                                    provider,
-                                    &mut settings,
-                                ) {
-                                    Ok(_) => {}
-                                    Err(e) => error_msg_clone.set(Some(e)),
-                                }
-                            }
-                            Err(e) => error_msg_clone.set(Some(e)),
-                        }
-                    } else {
-                        error_msg_clone.set(Some("No download URL".to_string()));
+                request_install(mod_store(), app_settings, mod_data(), mod_data().version.clone(), matches!(current_action, ButtonAction::Update), move |state| {
+                    if let JobState::Failed { error, .. } = state {
+                        error_msg.set(Some(error));
                     }
-                }
-                ButtonAction::Remove => {
-                    if let Some(local_name) = local_file_to_remove {
-                        let mut settings = settings_signal.write();
-                        match remove_mod(&folder, &local_name, &mut settings) {
-                            Ok(_) => {}
-                            Err(e) => error_msg_clone.set(Some(e)),
-                        }
-                    } else {
-                        error_msg_clone.set(Some("File not found locally".to_string()));
+                });
+            }
+            ButtonAction::Remove => {
+                if let Some(local_name) = local_file_to_remove {
+                    match request_remove(mod_store(), app_settings, &folder, &local_name) {
+                        Ok(_) => {}
+                        Err(e) => error_msg.set(Some(e)),
                     }
+                } else {
+                    error_msg.set(Some("File not found locally".to_string()));
                 }
-                _ => {}
             }
-            mod_store.write().set_processing(&mod_id, false);
-        });
+            _ => {}
+        }
     };
 
     let mut selected_image = use_signal(|| None::<String>);
@@ -208,6 +195,14 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                             }
                         },
                         div { style: "font-size: 14px; margin-top: 5px;", "By {mod_data().authors}" }
+                        if !mod_data().other_sources.is_empty() {
+                            {
+                                let names: Vec<&str> = mod_data().providers().iter().map(|p| p.display_name()).collect();
+                                rsx! {
+                                    div { style: "font-size: 12px; margin-top: 5px; color: var(--text-secondary);", "Available on: {names.join(\", \")}" }
+                                }
+                            }
+                        }
                     }
                     button { class: "btn btn-ghost", onclick: move |_| on_close.call(()), "X" }
                 }
@@ -226,6 +221,13 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                     }
                 }
 
+                if !mod_data().distribution_allowed {
+                    div {
+                        style: "background: var(--bg-tertiary); color: var(--text-secondary); padding: 12px; border-radius: 6px; font-size: 14px; text-align: center;",
+                        "The author only allows downloads from {mod_data().provider.display_name()}'s website. Installing opens the download page and picks the file up from your downloads folder."
+                    }
+                }
+
                 div { style: "padding: 0 30px; display: flex; gap: 10px; border-bottom: 1px solid var(--bg-tertiary);",
                     button {
                         class: if active_tab() == "overview" { "btn btn-tab-active" } else { "btn btn-tab" },
@@ -235,7 +237,7 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                     button {
                         class: if active_tab() == "versions" { "btn btn-tab-active" } else { "btn btn-tab" },
                         onclick: move |_| active_tab.set("versions"),
-                        "Versions ({displayed_versions.read().len()})"
+                        "{versions_label}"
                     }
                 }
 
@@ -293,13 +295,14 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                     } else if active_tab() == "versions" {
                         div { style: "display: flex; flex-direction: column; gap: 10px;",
                             for version in displayed_versions.read().iter() {{
-                                let mod_name_owned = mod_name_for_versions.clone();
                                 let mod_id_owned = mod_id_for_versions.clone();
                                 let version_data = version.clone();
 
                                 rsx!{
                                     VersionRow {
                                         version: version.clone(),
+                                        is_compatible: app_settings.read().get_target_game_version()
+                                            .is_none_or(|target| version.supports_game_version(&target)),
                                         is_installed: {
                                             let current_settings = app_settings.read();
                                             if let Some(entry) = current_settings.installed_mods.values().find(|e| e.mod_id == mod_id_owned) {
@@ -310,57 +313,30 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                                         },
                                         is_processing: is_processing,
                                         on_install: move |_| {
-                                            let mod_id = mod_id_owned.clone();
-                                            let mod_name = mod_name_owned.clone();
-                                            let folder = match app_settings.read().get_game_folder() {
-                                                Some(p) => p,
-                                                None => { error_msg.set(Some("No Game Folder Set".to_string())); return; }
-                                            };
-
-                                            let file_id = version_data.file_id.clone();
-                                            let file_name = version_data.file_name.clone();
-                                            let version_name = version_data.display_name.clone();
-                                            let provider = app_settings.read().api_provider.clone();
-
-                                            let mut error_msg_clone = error_msg.clone();
-                                            let mut settings_signal = app_settings.clone();
-                                            let version_clone_for_dl = version_data.clone();
-
-                                            (mod_store.write()).set_processing(&mod_id, true);
+                                            if app_settings.read().get_game_folder().is_none() {
+                                                error_msg.set(Some("No Game Folder Set".to_string()));
+                                                return;
+                                            }
                                             error_msg.set(None);
 
-                                            spawn(async move {
-                                                let download_res = {
-                                                    let settings = settings_signal.read();
-                                                    download_version_unified(&settings, &version_clone_for_dl).await
-                                                };
-
-                                                match download_res {
-                                                    Ok((_, bytes)) => {
-                                                        let mut settings = settings_signal.write();
-                                                        match install_mod(
-                                                            &folder,
-                                                            &file_name,
-                                                            &bytes,
-                                                            mod_id.clone(),
-                                                            mod_name,
-                                                            file_id,
-                                                            version_name,
-                                                            provider,
-                                                            &mut settings
-                                                        ) {
-                                                            Ok(_) => {},
-                                                            Err(e) => error_msg_clone.set(Some(e)),
-                                                        }
-                                                    }
-                                                    Err(e) => error_msg_clone.set(Some(e)),
+                                            request_install(mod_store(), app_settings, mod_data(), version_data.clone(), false, move |state| {
+                                                if let JobState::Failed { error, .. } = state {
+                                                    error_msg.set(Some(error));
                                                 }
-                                                (mod_store.write()).set_processing(&mod_id, false);
                                             });
                                         }
                                     }
                                 }
                             }}
+                            if has_older_versions() {
+                                button {
+                                    class: "btn btn-neutral",
+                                    style: "align-self: center; padding: 5px 15px; font-size: 12px;",
+                                    disabled: loading_versions(),
+                                    onclick: load_older_versions,
+                                    if loading_versions() { "Loading..." } else { "Load older versions" }
+                                }
+                            }
                         }
                     }
                 }
@@ -373,6 +349,18 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
                         onclick: handle_action,
                         "{button_info().text}"
                     }
+                    if install_info().install_status != InstallStatus::NotInstalled && !button_info().disabled {
+                        RollbackButton {
+                            provider: mod_data().provider.clone(),
+                            mod_id: mod_data().id.clone(),
+                            on_error: move |e| error_msg.set(Some(e)),
+                        }
+                    }
+                    if let Some(progress) = mod_store.read().progress(&mod_data().id) {
+                        div { style: "flex: 1; align-self: center;",
+                            DownloadProgressBar { progress }
+                        }
+                    }
                 }
             }
         }
@@ -382,6 +370,7 @@ pub fn ModInfoDialog(mod_data: UiMod, on_close: EventHandler<()>) -> Element {
 #[component]
 fn VersionRow(
     version: UiModVersion,
+    is_compatible: bool,
     is_installed: bool,
     is_processing: bool,
     on_install: EventHandler<UiModVersion>,
@@ -406,6 +395,10 @@ fn VersionRow(
                     span { "{version.upload_date}" }
                     span { "•" }
                     span { "{version.game_versions.join(\", \")}" }
+                    if !is_compatible {
+                        span { "•" }
+                        span { style: "color: var(--warning);", "Not built for your target version" }
+                    }
                 }
             }
             button {
@@ -0,0 +1,72 @@
+use dioxus::prelude::*;
+use crate::api::local_mods::{remove_mod, remove_mod_with_dependents};
+use crate::api::settings::AppSettings;
+use crate::state::mod_store::PendingRemoval;
+
+#[component]
+pub fn RemoveModDialog(removal: PendingRemoval, on_close: EventHandler<()>) -> Element {
+    let mut app_settings = use_context::<Signal<AppSettings>>();
+    let mut error_msg = use_signal(|| Option::<String>::None);
+
+    let mut remove = move |with_dependents: bool, file_name: String| {
+        let Some(folder) = app_settings.read().get_game_folder() else {
+            error_msg.set(Some("No Game Folder Set".to_string()));
+            return;
+        };
+        let result = if with_dependents {
+            remove_mod_with_dependents(&folder, &file_name, &mut app_settings.write()).map(|_| ())
+        } else {
+            remove_mod(&folder, &file_name, &mut app_settings.write())
+        };
+        match result {
+            Ok(()) => on_close.call(()),
+            Err(e) => error_msg.set(Some(e)),
+        }
+    };
+
+    let all_name = removal.file_name.clone();
+    let only_name = removal.file_name.clone();
+
+    rsx! {
+        div {
+            style: "position: fixed; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.7); display: flex; align-items: center; justify-content: center; z-index: 100;",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                style: "background-color: var(--bg-tertiary); width: 460px; padding: 25px; border-radius: 10px; display: flex; flex-direction: column; gap: 15px; border: 1px solid var(--border-color); box-shadow: 0 4px 15px rgba(0,0,0,0.5);",
+                onclick: |e| e.stop_propagation(),
+
+                h3 { style: "margin: 0; color: var(--text-primary);", "Remove {removal.mod_name}?" }
+                span { style: "font-size: 13px; color: var(--text-secondary);",
+                    "These installed mods depend on it and may stop working:"
+                }
+                div { style: "display: flex; flex-direction: column; gap: 4px; font-size: 13px; color: var(--text-primary);",
+                    for dependent in removal.dependents.iter().cloned() {
+                        div { key: "{dependent}", "{dependent}" }
+                    }
+                }
+                if let Some(err) = error_msg() {
+                    span { style: "font-size: 12px; color: var(--danger);", "{err}" }
+                }
+
+                div { style: "display: flex; gap: 10px; justify-content: flex-end;",
+                    button {
+                        class: "btn btn-neutral",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+                    button {
+                        class: "btn btn-warning",
+                        onclick: move |_| remove(false, only_name.clone()),
+                        "Remove only this"
+                    }
+                    button {
+                        class: "btn btn-danger",
+                        onclick: move |_| remove(true, all_name.clone()),
+                        "Remove all {removal.dependents.len() + 1}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@ use dioxus::prelude::*;
 use std::path::{Path, PathBuf};
 use crate::api::settings::{AppSettings, AppTheme};
 use crate::components::drop_down::{ThemeDropdown};
+use crate::state::mod_store::ModStore;
 use crate::SidebarTab;
 
 fn truncate_path(path: &Path, max_chars: usize) -> String {
@@ -22,6 +23,7 @@ pub fn Sidebar(
     on_open_api: EventHandler<()>,
 ) -> Element {
     let mut settings_store = use_context::<Signal<AppSettings>>();
+    let mod_store = use_context::<Signal<ModStore>>();
 
     let game_folder = settings_store().get_game_folder();
     let pick_folder = move |_| {
@@ -38,6 +40,9 @@ pub fn Sidebar(
         String::new()
     };
 
+    let active_jobs = mod_store.read().jobs.read().iter().filter(|j| j.state.is_active()).count();
+    let tasks_label = if active_jobs > 0 { format!("Tasks ({})", active_jobs) } else { "Tasks".to_string() };
+
     let current_theme_label = match settings_store().get_theme() {
         AppTheme::Dark => "Dark Mode",
         AppTheme::Light => "Light Mode",
@@ -63,6 +68,12 @@ pub fn Sidebar(
                 onclick: move |_| active_tab.set(SidebarTab::Installed)
             }
 
+            SidebarBtn {
+                label: tasks_label,
+                active: active_tab() == SidebarTab::Tasks,
+                onclick: move |_| active_tab.set(SidebarTab::Tasks)
+            }
+
             div { style: "flex: 1;" }
 
             span { style: "font-size: 12px; color: var(--text-secondary); display: block; margin-bottom: 5px;", "Theme" }
@@ -94,7 +105,7 @@ pub fn Sidebar(
 }
 
 #[component]
-fn SidebarBtn(label: &'static str, active: bool, onclick: EventHandler<MouseEvent>) -> Element {
+fn SidebarBtn(#[props(into)] label: String, active: bool, onclick: EventHandler<MouseEvent>) -> Element {
     let active_class = if active { "active" } else { "" };
     rsx! {
         button {
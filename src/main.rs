@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 
-pub mod api;
+pub use hytale_mod_manager::api;
 pub mod components;
 pub mod pages;
 pub mod style;
@@ -8,14 +8,19 @@ mod state;
 
 use dioxus::desktop::{Config, WindowBuilder};
 use dioxus::prelude::*;
+use dioxus::html::HasFileData;
 use crate::api::settings::{AppSettings, AppTheme};
 use crate::api::ui_mod::UiMod;
 use crate::components::sidebar::Sidebar;
 use crate::components::mod_info::ModInfoDialog;
 use crate::components::api_dialog::ApiDialog;
+use crate::components::install_plan::InstallPlanDialog;
+use crate::components::remove_dialog::RemoveModDialog;
+use crate::components::manual_download::ManualDownloadDialog;
 use crate::pages::search::SearchPage;
 use crate::pages::installed::InstalledPage;
-use crate::state::mod_store::ModStore;
+use crate::pages::tasks::TasksPage;
+use crate::state::mod_store::{install_files, watch_queue, ModStore};
 
 #[derive(Clone, PartialEq)]
 pub enum ActiveModal {
@@ -28,6 +33,7 @@ pub enum ActiveModal {
 pub enum SidebarTab {
     SearchMods,
     Installed,
+    Tasks,
 }
 
 fn main() {
@@ -51,8 +57,9 @@ fn App() -> Element {
 
     use_context_provider(|| mod_store);
     use_context_provider(|| settings_store);
+    use_hook(|| watch_queue(mod_store));
 
-    let active_tab = use_signal(|| SidebarTab::SearchMods);
+    let mut active_tab = use_signal(|| SidebarTab::SearchMods);
     let mut active_modal = use_signal(|| ActiveModal::None);
 
 
@@ -66,6 +73,18 @@ fn App() -> Element {
         }
     });
 
+    // Jars and zips dropped anywhere on the window are installed like files
+    // picked with "Install from file".
+    let drop_files = move |e: Event<DragData>| {
+        e.prevent_default();
+        let paths: Vec<_> = e.files().into_iter().map(|f| f.path()).collect();
+        if paths.is_empty() {
+            return;
+        }
+        install_files(mod_store(), settings_store, paths);
+        active_tab.set(SidebarTab::Installed);
+    };
+
     let theme_class = match settings_store().get_theme() {
         AppTheme::Dark => {"dark-theme"}
         AppTheme::Light => {"light-theme"}
@@ -77,6 +96,8 @@ fn App() -> Element {
         div {
             class: "{theme_class}",
             style: "display: flex; height: 100vh; width: 100vw;",
+            ondragover: |e| e.prevent_default(),
+            ondrop: drop_files,
 
             Sidebar {
                 active_tab: active_tab,
@@ -96,6 +117,9 @@ fn App() -> Element {
                         InstalledPage {
                             on_open_info: move |m: UiMod| active_modal.set(ActiveModal::ModInfo(m))
                         }
+                    },
+                    SidebarTab::Tasks => rsx! {
+                        TasksPage {}
                     }
                 }
             }
@@ -112,6 +136,27 @@ fn App() -> Element {
                 },
                 ActiveModal::None => rsx! {}
             }
+
+            if let Some(plan) = mod_store().pending_plan.read().clone() {
+                InstallPlanDialog {
+                    plan,
+                    on_close: move |_| mod_store().pending_plan.set(None),
+                }
+            }
+
+            if let Some(removal) = mod_store().pending_removal.read().clone() {
+                RemoveModDialog {
+                    removal,
+                    on_close: move |_| mod_store().pending_removal.set(None),
+                }
+            }
+
+            if let Some(pending) = mod_store().pending_manual.read().clone() {
+                ManualDownloadDialog {
+                    pending,
+                    on_close: move |_| mod_store().pending_manual.set(None),
+                }
+            }
         }
     }
 }
\ No newline at end of file
@@ -1,91 +1,119 @@
-use crate::api::ui_mod::get_mod_details_unified;
-use crate::api::local_mods::extract_base_name;
-use crate::api::settings::AppSettings;
+use crate::api::dependencies::audit_conflicts;
+use crate::api::ui_mod::get_mod_details_batch_unified;
+use crate::api::local_mods::{extract_base_name, find_orphans, remove_mod, scan_installed};
+use crate::api::settings::{ApiProvider, AppSettings, InstalledModEntry};
 use crate::api::ui_mod::{UiMod, UiModVersion};
 use crate::components::mod_card::ModCard;
-use crate::state::mod_store::ModStore;
+use crate::state::mod_store::{install_files, ModStore};
 use dioxus::prelude::*;
-use std::fs;
-use std::path::Path;
+use std::collections::HashMap;
+
+/// What the page can show about a file before any provider has answered.
+fn placeholder_mod(filename: &str, known: Option<&InstalledModEntry>, active_provider: &ApiProvider) -> UiMod {
+    let (base_name, local_version) = extract_base_name(filename);
+
+    let (id, display_name, provider) = if let Some(known) = known {
+        (known.mod_id.clone(), known.mod_name.clone(), known.api_provider.clone())
+    } else {
+        ("0".to_string(), base_name.replace("-", " "), active_provider.clone())
+    };
+
+    UiMod {
+        id,
+        name: display_name,
+        slug: base_name.to_lowercase(),
+        summary: format!("Local file: {}", filename),
+        authors: "Local Install".to_string(),
+        download_count: 0,
+        icon: String::new(),
+        categories: vec![],
+        version: UiModVersion {
+            file_id: "0".to_string(),
+            file_name: filename.to_string(),
+            display_name: local_version,
+            download_url: None,
+            release_type: 1,
+            upload_date: "Local".to_string(),
+            game_versions: vec![],
+            file_size: None,
+            checksums: vec![],
+            dependencies: vec![],
+        },
+        gallery_urls: vec![],
+        website_url: String::new(),
+        class_id: None,
+        banner: String::new(),
+        provider,
+        other_sources: vec![],
+        distribution_allowed: true,
+    }
+}
 
 #[component]
 pub fn InstalledPage(on_open_info: EventHandler<UiMod>) -> Element {
-    let app_settings = use_context::<Signal<AppSettings>>();
+    let mut app_settings = use_context::<Signal<AppSettings>>();
+    let mod_store = use_context::<Signal<ModStore>>();
 
     let mut refresh_trigger = use_signal(|| 0);
     let mut query = use_signal(|| String::new());
     let mut display_list = use_signal(|| Vec::<UiMod>::new());
     let mut is_scanning = use_signal(|| false);
+    let mut pending_batches = use_signal(|| 0usize);
+    let mut scan_generation = use_signal(|| 0u32);
 
     use_resource(move || async move {
         let _ = refresh_trigger();
+        let _ = (mod_store().refresh_trigger)();
         is_scanning.set(true);
+        let generation = *scan_generation.peek() + 1;
+        scan_generation.set(generation);
 
-        let (folder_opt, installed_map) = {
+        let (folder_opt, installed_map, active_provider) = {
             let s = app_settings.peek();
-            (s.get_game_folder(), s.installed_mods.clone())
+            (s.get_game_folder(), s.installed_mods.clone(), s.get_api_provider())
         };
 
         let mut new_list: Vec<UiMod> = Vec::new();
+        let mut lookups: HashMap<ApiProvider, Vec<(ApiProvider, String)>> = HashMap::new();
 
         if let Some(folder) = folder_opt {
-            let mods_path = Path::new(&folder).join("UserData").join("Mods");
+            for filename in scan_installed(&folder, &app_settings.peek()) {
+                let known = installed_map.get(&filename);
+
+                // Files installed from disk have no provider to ask.
+                if let Some(known) = known.filter(|k| k.api_provider != ApiProvider::Local) {
+                    lookups.entry(known.api_provider.clone())
+                        .or_default()
+                        .push((known.api_provider.clone(), known.mod_id.clone()));
+                }
 
-            if !mods_path.exists() {
-                let _ = fs::create_dir_all(&mods_path);
+                new_list.push(placeholder_mod(&filename, known, &active_provider));
             }
+        }
 
-            if let Ok(entries) = fs::read_dir(&mods_path) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_file() && path.extension().map_or(false, |ext| ext == "jar" || ext == "zip") {
-                        let filename = entry.file_name().to_string_lossy().into_owned();
+        new_list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
 
-                        if let Some(known_mod) = installed_map.get(&filename) {
-                            if let Some(ui_mod) = get_mod_details_unified(&known_mod.api_provider, &known_mod.mod_id).await {
-                                new_list.push(ui_mod);
-                                continue;
-                            }
-                        }
+        // Show what the manifest knows right away, then fill in each
+        // provider's details as its batch comes back.
+        display_list.set(new_list);
+        is_scanning.set(false);
+        pending_batches.set(lookups.len());
 
-                        let (base_name, local_version) = extract_base_name(&filename);
+        for (_, batch) in lookups {
+            spawn(async move {
+                let details = get_mod_details_batch_unified(&batch).await;
 
-                        let (id, display_name) = if let Some(known) = installed_map.get(&filename) {
-                            (known.mod_id.clone(), known.mod_name.clone())
-                        } else {
-                            ("0".to_string(), base_name.replace("-", " "))
-                        };
-
-                        new_list.push(UiMod {
-                            id,
-                            name: display_name,
-                            summary: format!("Local file: {}", filename),
-                            authors: "Local Install".to_string(),
-                            download_count: 0,
-                            icon: String::new(),
-                            categories: vec![],
-                            version: UiModVersion {
-                                file_id: "0".to_string(),
-                                file_name: filename.clone(),
-                                display_name: local_version,
-                                download_url: None,
-                                release_type: 1,
-                                upload_date: "Local".to_string(),
-                                game_versions: vec![],
-                            },
-                            gallery_urls: vec![],
-                            website_url: String::new(),
-                            banner: String::new(),
-                        });
+                if *scan_generation.peek() != generation {
+                    return;
+                }
+                for m in display_list.write().iter_mut() {
+                    if let Some(ui_mod) = details.get(&(m.provider.clone(), m.id.clone())) {
+                        *m = ui_mod.clone();
                     }
                 }
-            }
+                pending_batches -= 1;
+            });
         }
-
-        new_list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-
-        display_list.set(new_list);
-        is_scanning.set(false);
     });
 
     let filtered_mods = use_memo(move || {
@@ -101,6 +129,28 @@ pub fn InstalledPage(on_open_info: EventHandler<UiMod>) -> Element {
         }
     });
 
+    let orphans = use_memo(move || find_orphans(&app_settings.read()));
+    let conflicts = use_memo(move || audit_conflicts(&app_settings.read()));
+
+    let clean_up_orphans = move |_| {
+        let Some(folder) = app_settings.read().get_game_folder() else { return };
+        for file_name in orphans() {
+            if let Err(e) = remove_mod(&folder, &file_name, &mut app_settings.write()) {
+                eprintln!("Failed to remove {}: {}", file_name, e);
+            }
+        }
+        refresh_trigger += 1;
+    };
+    let orphan_list = orphans().join("\n");
+
+    let pick_files = move |_| {
+        spawn(async move {
+            let Some(files) = rfd::AsyncFileDialog::new().add_filter("Mods", &["jar", "zip"]).pick_files().await else { return };
+            let paths = files.iter().map(|f| f.path().to_owned()).collect();
+            install_files(mod_store(), app_settings, paths);
+        });
+    };
+
     rsx! {
         div { style: "display: flex; flex-direction: column; height: 100%;",
             h2 { style: "color: var(--text-primary); margin-top: 0;", "Installed Mods" }
@@ -116,8 +166,46 @@ pub fn InstalledPage(on_open_info: EventHandler<UiMod>) -> Element {
                     onclick: move |_| refresh_trigger += 1,
                     "Refresh"
                 }
+                button {
+                    class: "btn btn-brand",
+                    title: "Install a .jar or .zip you already have. You can also drop files onto the window.",
+                    onclick: pick_files,
+                    "Install from file"
+                }
+                if !orphans().is_empty() {
+                    button {
+                        class: "btn btn-warning",
+                        title: "No installed mod needs these any more:\n{orphan_list}",
+                        onclick: clean_up_orphans,
+                        "Remove {orphans().len()} unused"
+                    }
+                }
+            }
+
+            if !mod_store().file_errors.read().is_empty() {
+                div { style: "display: flex; align-items: flex-start; gap: 10px; padding: 10px; margin-bottom: 10px; border-radius: 8px; border: 1px solid var(--danger); color: var(--danger); font-size: 13px;",
+                    div { style: "flex: 1; display: flex; flex-direction: column; gap: 4px;",
+                        for err in mod_store().file_errors.cloned() {
+                            span { "{err}" }
+                        }
+                    }
+                    button {
+                        class: "btn btn-ghost",
+                        onclick: move |_| mod_store().file_errors.set(Vec::new()),
+                        "Dismiss"
+                    }
+                }
             }
 
+            if !conflicts().is_empty() {
+                div { style: "display: flex; flex-direction: column; gap: 4px; padding: 10px; margin-bottom: 10px; border-radius: 8px; border: 1px solid var(--warning); color: var(--warning); font-size: 13px;",
+                    for conflict in conflicts() {
+                        span { key: "{conflict.mod_id}-{conflict.other_mod_id}",
+                            "⚠ {conflict.mod_name} is incompatible with {conflict.other_mod_name}"
+                        }
+                    }
+                }
+            }
             div { style: "flex: 1; overflow-y: auto; padding-right: 5px; margin-bottom: 10px;",
                 if is_scanning() {
                      div { style: "display: flex; flex-direction: column; align-items: center; justify-content: center; height: 50%; color: var(--text-secondary); gap: 10px;",
@@ -144,8 +232,13 @@ pub fn InstalledPage(on_open_info: EventHandler<UiMod>) -> Element {
                     }
                 }
             }
-            div { style: "padding: 10px; background-color: var(--bg-tertiary); border-radius: 8px; font-size: 12px; color: var(--text-secondary);",
-                if let Some(folder) = app_settings.read().get_game_folder() { "Location: {folder.display()}" } else { "No folder selected" }
+            div { style: "display: flex; justify-content: space-between; padding: 10px; background-color: var(--bg-tertiary); border-radius: 8px; font-size: 12px; color: var(--text-secondary);",
+                span {
+                    if let Some(folder) = app_settings.read().get_game_folder() { "Location: {folder.display()}" } else { "No folder selected" }
+                }
+                if pending_batches() > 0 {
+                    span { "Fetching mod details..." }
+                }
             }
         }
     }
@@ -1,2 +1,3 @@
 pub mod search;
-pub mod installed;
\ No newline at end of file
+pub mod installed;
+pub mod tasks;
\ No newline at end of file
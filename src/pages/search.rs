@@ -1,5 +1,6 @@
 use dioxus::prelude::*;
-use crate::api::ui_mod::search_mods_unified;
+use crate::api::federated::search_mods_federated;
+use crate::api::ui_mod::{get_categories_unified, search_mods_unified, SearchFilter, UiCategory};
 use crate::api::local_mods::{check_install_status, InstallStatus};
 use crate::api::ui_mod::UiMod;
 use crate::api::settings::AppSettings;
@@ -9,15 +10,59 @@ use crate::state::mod_store::ModStore;
 
 const PAGE_SIZE: u32 = 20;
 
+/// Classes first, each followed by its categories, so the picker reads as a tree.
+fn category_tree(categories: &[UiCategory]) -> Vec<UiCategory> {
+    let mut ordered = Vec::new();
+    for class in categories.iter().filter(|c| c.is_class) {
+        ordered.push(class.clone());
+        let mut children: Vec<_> = categories.iter()
+            .filter(|c| !c.is_class && c.class_id.as_ref() == Some(&class.id))
+            .cloned()
+            .collect();
+        children.sort_by_key(|c| c.name.to_lowercase());
+        ordered.extend(children);
+    }
+    ordered
+}
+
 #[component]
 pub fn SearchPage(on_open_info: EventHandler<UiMod>) -> Element {
     let mut mod_store = use_context::<Signal<ModStore>>();
-    let app_settings = use_context::<Signal<AppSettings>>();
+    let mut app_settings = use_context::<Signal<AppSettings>>();
 
     let mut query = use_signal(|| String::new());
     let mut search_sort = use_signal(|| 0);
     let mut page_index = use_signal(|| 0);
     let mut search_trigger = use_signal(|| 0);
+    let mut search_filter = use_signal(SearchFilter::default);
+
+    let categories_resource = use_resource(move || async move {
+        let api_provider = app_settings.read().get_api_provider();
+        match get_categories_unified(&api_provider).await {
+            Ok(categories) => category_tree(&categories),
+            Err(e) => {
+                eprintln!("Failed to load categories: {}", e);
+                vec![]
+            }
+        }
+    });
+
+    let categories = categories_resource.read().clone().unwrap_or_default();
+
+    let selected_category = categories.iter()
+        .position(|c| c.as_filter() == search_filter())
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    // Category ids belong to one provider, so a switch clears the filter.
+    let mut filter_provider = use_signal(|| app_settings.peek().get_api_provider());
+    use_effect(move || {
+        let api_provider = app_settings.read().get_api_provider();
+        if *filter_provider.peek() != api_provider {
+            filter_provider.set(api_provider);
+            search_filter.set(SearchFilter::default());
+        }
+    });
 
     let search_resource = use_resource(move || async move {
         let _ = search_trigger();
@@ -26,8 +71,15 @@ pub fn SearchPage(on_open_info: EventHandler<UiMod>) -> Element {
         let settings = app_settings.read().clone();
         let offset = current_idx * PAGE_SIZE;
         let sort = search_sort();
-        
-        match search_mods_unified(&settings, sort, query, offset).await {
+        let filter = search_filter();
+
+        let result = if settings.is_federated_search() {
+            search_mods_federated(&settings, sort, query, offset, &filter).await
+        } else {
+            search_mods_unified(&settings, sort, query, offset, &filter).await
+        };
+
+        match result {
             Ok((ui_mods, total)) => (ui_mods, total),
             Err(_) => (vec![], 0)
         }
@@ -87,6 +139,41 @@ pub fn SearchPage(on_open_info: EventHandler<UiMod>) -> Element {
                         on_select: move |c: usize| search_sort.set(c as u32),
                     }
                 }
+                if !categories.is_empty() {
+                    div { style: "flex: 2; min-width: 0;",
+                        ThemeDropdown {
+                            items: std::iter::once("All Categories".to_string())
+                                .chain(categories.iter().map(|c| {
+                                    if c.is_class { c.name.clone() } else { format!("  {}", c.name) }
+                                }))
+                                .collect::<Vec<_>>(),
+                            upwards: false,
+                            placeholder: "Category",
+                            default_index: Some(selected_category),
+                            on_select: move |i: usize| {
+                                let filter = match i {
+                                    0 => SearchFilter::default(),
+                                    i => categories_resource.read().as_ref()
+                                        .and_then(|c| c.get(i - 1))
+                                        .map(|c| c.as_filter())
+                                        .unwrap_or_default(),
+                                };
+                                search_filter.set(filter);
+                                page_index.set(0);
+                            },
+                        }
+                    }
+                }
+                button {
+                    class: if app_settings.read().is_federated_search() { "btn btn-neutral active" } else { "btn btn-neutral" },
+                    title: "Search every configured provider at once",
+                    onclick: move |_| {
+                        let enabled = !app_settings.read().is_federated_search();
+                        app_settings.write().set_federated_search(enabled);
+                        page_index.set(0);
+                    },
+                    "All Providers"
+                }
                 button { class: "btn btn-brand", onclick: move |_| *search_trigger.write() += 1, "Search" }
             }
 
@@ -100,7 +187,7 @@ pub fn SearchPage(on_open_info: EventHandler<UiMod>) -> Element {
                     div {
                         style: "display: flex; flex-direction: column; gap: 10px;",
                         for m in mods {
-                            ModCard { key: "{m.id}", mod_data: m, onclick: on_open_info }
+                            ModCard { key: "{m.provider:?}-{m.id}", mod_data: m, onclick: on_open_info }
                         }
                     }
                 } else {
@@ -0,0 +1,96 @@
+use dioxus::prelude::*;
+use crate::api::queue::{install_queue, Job, JobState};
+use crate::api::settings::AppSettings;
+use crate::components::mod_card::DownloadProgressBar;
+use crate::state::mod_store::{start_job, ModStore};
+
+#[component]
+pub fn TasksPage() -> Element {
+    let mod_store = use_context::<Signal<ModStore>>();
+    let app_settings = use_context::<Signal<AppSettings>>();
+
+    let jobs = mod_store.read().jobs;
+    let has_finished = jobs.read().iter().any(|j| !j.state.is_active());
+    let parallel = app_settings.read().get_max_parallel_downloads();
+
+    rsx! {
+        div { style: "display: flex; flex-direction: column; height: 100%;",
+            h2 { style: "color: var(--text-primary); margin-top: 0;", "Tasks" }
+            div { style: "display: flex; align-items: center; gap: 10px; margin-bottom: 20px;",
+                span { style: "flex: 1; font-size: 12px; color: var(--text-secondary);",
+                    "Up to {parallel} downloads run at once."
+                }
+                button {
+                    class: "btn btn-secondary",
+                    disabled: !has_finished,
+                    onclick: move |_| install_queue().clear_finished(),
+                    "Clear Finished"
+                }
+            }
+
+            div { style: "flex: 1; overflow-y: auto; padding-right: 5px; display: flex; flex-direction: column; gap: 10px;",
+                if jobs.read().is_empty() {
+                    div { style: "display: flex; align-items: center; justify-content: center; height: 50%; color: var(--text-secondary);",
+                        "No downloads yet."
+                    }
+                }
+                for job in jobs.read().iter().rev().cloned() {
+                    TaskRow { key: "{job.id}", job, settings: app_settings }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn TaskRow(job: Job, settings: Signal<AppSettings>) -> Element {
+    let job_id = job.id;
+    let state_color = match job.state {
+        JobState::Failed { .. } => "var(--danger)",
+        JobState::Cancelled => "var(--warning)",
+        JobState::Done => "var(--success)",
+        _ => "var(--text-secondary)",
+    };
+
+    rsx! {
+        div { style: "background-color: var(--bg-tertiary); padding: 10px; border-radius: 8px; display: flex; align-items: center; gap: 15px;",
+            div { style: "flex: 1; display: flex; flex-direction: column; gap: 2px; min-width: 0;",
+                div { style: "display: flex; align-items: center; gap: 8px;",
+                    span { style: "font-weight: bold; color: var(--text-primary);", "{job.mod_name}" }
+                    span { style: "font-size: 12px; color: var(--text-secondary);", "{job.version.display_name}" }
+                    if let Some(required_by) = &job.required_by {
+                        span { style: "font-size: 11px; color: var(--text-secondary);", "for {required_by}" }
+                    }
+                    span { style: "font-size: 10px; color: {state_color};", "{job.state.label()}" }
+                }
+                match &job.state {
+                    JobState::Downloading { progress } => rsx! { DownloadProgressBar { progress: *progress } },
+                    JobState::Failed { error, .. } => rsx! {
+                        span { style: "font-size: 11px; color: var(--danger); overflow-wrap: anywhere;", "{error}" }
+                    },
+                    _ => rsx! {
+                        span { style: "font-size: 11px; color: var(--text-secondary);", "{job.version.file_name}" }
+                    },
+                }
+            }
+            if job.state.can_cancel() {
+                button {
+                    class: "btn btn-ghost",
+                    onclick: move |_| { install_queue().cancel(job_id); },
+                    "Cancel"
+                }
+            }
+            if job.state.can_retry() {
+                button {
+                    class: "btn btn-brand",
+                    onclick: move |_| {
+                        if install_queue().retry(job_id) {
+                            start_job(settings, job_id, |_| {});
+                        }
+                    },
+                    "Retry"
+                }
+            }
+        }
+    }
+}
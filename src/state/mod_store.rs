@@ -1,12 +1,45 @@
 use dioxus::prelude::*;
 use std::collections::{HashMap, HashSet};
-use crate::api::local_mods::{ModInstallInfo, check_install_status, InstallStatus};
+use std::path::{Path, PathBuf};
+use crate::api::dependencies::{resolve_install_plan, InstallPlan};
+use crate::api::download::DownloadProgress;
+use crate::api::queue::{install_job, install_queue, Job, JobId, JobState};
+use crate::api::local_file::install_from_file;
+use crate::api::local_mods::{find_dependents, remove_mod, ModInstallInfo, check_install_status, InstallStatus};
+use crate::api::routing::ContentType;
 use crate::api::settings::AppSettings;
+use crate::api::ui_mod::{UiMod, UiModVersion};
+
+/// A removal that would break other installed mods, waiting for the user to
+/// decide what to do with them.
+#[derive(Clone, PartialEq)]
+pub struct PendingRemoval {
+    pub file_name: String,
+    pub mod_name: String,
+    /// File names of the installed mods that need it.
+    pub dependents: Vec<String>,
+}
+
+/// A mod the provider won't serve to the manager, waiting for the user to
+/// download it by hand.
+#[derive(Clone, PartialEq)]
+pub struct PendingManual {
+    pub ui_mod: UiMod,
+    pub version: UiModVersion,
+}
 
 #[derive(Clone, Copy)]
 pub struct ModStore {
     pub status_cache: Signal<HashMap<String, ModInstallInfo>>,
     pub processing_ids: Signal<HashSet<String>>,
+    /// Mirror of the install queue, refreshed by `watch_queue`.
+    pub jobs: Signal<Vec<Job>>,
+    /// An install that pulls in other mods, waiting for the user to confirm it.
+    pub pending_plan: Signal<Option<InstallPlan>>,
+    pub pending_removal: Signal<Option<PendingRemoval>>,
+    pub pending_manual: Signal<Option<PendingManual>>,
+    /// Why files picked or dropped onto the window couldn't be installed.
+    pub file_errors: Signal<Vec<String>>,
     pub refresh_trigger: Signal<u32>,
 }
 
@@ -15,6 +48,11 @@ impl ModStore {
         Self {
             status_cache: Signal::new(HashMap::new()),
             processing_ids: Signal::new(HashSet::new()),
+            jobs: Signal::new(Vec::new()),
+            pending_plan: Signal::new(None),
+            pending_removal: Signal::new(None),
+            pending_manual: Signal::new(None),
+            file_errors: Signal::new(Vec::new()),
             refresh_trigger: Signal::new(0),
         }
     }
@@ -48,8 +86,9 @@ impl ModStore {
         self.status_cache.write().remove(mod_id);
     }
 
+    /// True while a removal is running or the queue has an active job for the mod.
     pub fn is_processing(&self, mod_id: &str) -> bool {
-        self.processing_ids.read().contains(mod_id)
+        self.processing_ids.read().contains(mod_id) || self.active_job(mod_id).is_some()
     }
 
     pub fn set_processing(&mut self, mod_id: &str, is_loading: bool) {
@@ -60,7 +99,131 @@ impl ModStore {
         }
     }
 
+    pub fn active_job(&self, mod_id: &str) -> Option<Job> {
+        self.jobs.read().iter().find(|j| j.mod_id == mod_id && j.state.is_active()).cloned()
+    }
+
+    pub fn progress(&self, mod_id: &str) -> Option<DownloadProgress> {
+        match self.active_job(mod_id)?.state {
+            JobState::Downloading { progress } => Some(progress),
+            _ => None,
+        }
+    }
+
     pub fn trigger_refresh(&mut self) {
         self.refresh_trigger += 1;
     }
-}
\ No newline at end of file
+}
+
+/// Keeps `ModStore.jobs` in step with the install queue for the lifetime of the app.
+pub fn watch_queue(store: Signal<ModStore>) {
+    let mut jobs = store.peek().jobs;
+    spawn(async move {
+        let mut changes = install_queue().subscribe();
+        loop {
+            jobs.set(install_queue().jobs());
+            if changes.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Runs a queued job in the background, installing into the current game
+/// folder. `on_finish` gets the state the job ended in.
+pub fn start_job(mut settings: Signal<AppSettings>, job_id: JobId, on_finish: impl FnOnce(JobState) + 'static) {
+    let Some(folder) = settings.peek().get_game_folder() else {
+        install_queue().cancel(job_id);
+        on_finish(JobState::Cancelled);
+        return;
+    };
+
+    spawn(async move {
+        let state = install_queue().run(job_id, &folder, |job, file| {
+            install_job(&folder, job, file, &mut settings.write())
+        }).await;
+        on_finish(state);
+    });
+}
+
+/// Queues every install in `plan` and starts them. `on_finish` gets the state
+/// the target's job ended in; dependencies report through the Tasks page.
+pub fn start_plan(settings: Signal<AppSettings>, plan: &InstallPlan, on_finish: impl FnOnce(JobState) + 'static) {
+    let mut job_ids = plan.enqueue(install_queue()).into_iter();
+    if let Some(target) = job_ids.next() {
+        start_job(settings, target, on_finish);
+    }
+    for job_id in job_ids {
+        start_job(settings, job_id, |_| {});
+    }
+}
+
+/// Works out what installing `version` involves. A mod that needs nothing
+/// else and clashes with nothing starts downloading right away; otherwise the
+/// plan is parked in `pending_plan` until the user confirms it. Mods the
+/// provider only serves through its website go to `pending_manual` instead.
+/// `update` keeps a mod installed as a dependency marked as one.
+pub fn request_install(
+    mut store: ModStore,
+    settings: Signal<AppSettings>,
+    ui_mod: UiMod,
+    version: UiModVersion,
+    update: bool,
+    on_finish: impl FnOnce(JobState) + 'static,
+) {
+    if ui_mod.needs_manual_download(&version) {
+        store.pending_manual.set(Some(PendingManual { ui_mod, version }));
+        return;
+    }
+
+    store.set_processing(&ui_mod.id, true);
+    spawn(async move {
+        let snapshot = settings.peek().clone();
+        let mut plan = resolve_install_plan(&ui_mod, version, &snapshot).await;
+        plan.update = update;
+        store.set_processing(&ui_mod.id, false);
+
+        if plan.needs_review() {
+            store.pending_plan.set(Some(plan));
+        } else {
+            start_plan(settings, &plan, on_finish);
+        }
+    });
+}
+
+/// Installs jars and zips the user already has, from the file dialog or
+/// dropped onto the window. Files that fail are listed in `file_errors`.
+pub fn install_files(mut store: ModStore, mut settings: Signal<AppSettings>, paths: Vec<PathBuf>) {
+    let Some(folder) = settings.peek().get_game_folder() else {
+        store.file_errors.set(vec!["No Game Folder Set".to_string()]);
+        return;
+    };
+
+    let mut errors = Vec::new();
+    for path in paths {
+        match install_from_file(&folder, &path, ContentType::Mod, &mut settings.write()) {
+            Ok(info) => store.remove_mod(&info.mod_id),
+            Err(e) => errors.push(e),
+        }
+    }
+    store.file_errors.set(errors);
+    store.trigger_refresh();
+}
+
+/// Removes `file_name` unless other installed mods depend on it, in which case
+/// the removal is parked in `pending_removal` for the user to confirm.
+pub fn request_remove(mut store: ModStore, mut settings: Signal<AppSettings>, folder: &Path, file_name: &str) -> Result<(), String> {
+    let dependents = find_dependents(&settings.peek(), file_name);
+    if dependents.is_empty() {
+        return remove_mod(folder, file_name, &mut settings.write());
+    }
+
+    let mod_name = settings.peek().get_installed_mod(file_name)
+        .map_or_else(|| file_name.to_string(), |e| e.mod_name.clone());
+    store.pending_removal.set(Some(PendingRemoval {
+        file_name: file_name.to_string(),
+        mod_name,
+        dependents,
+    }));
+    Ok(())
+}
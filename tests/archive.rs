@@ -0,0 +1,86 @@
+//! Replaced versions are archived and can be put back without the network.
+
+mod common;
+
+use std::fs;
+use common::TestEnv;
+use hytale_mod_manager::api::archive::{archived_versions, get_archive_dir, MAX_ARCHIVED_VERSIONS};
+use hytale_mod_manager::api::local_mods::{install_mod, roll_back};
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+
+#[test]
+fn update_archives_and_rollback_restores() {
+    let env = TestEnv::new("archive-rollback");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+    env.install_mod(&mut settings, "1.2.0").unwrap();
+
+    let archived = archived_versions(&settings, &ApiProvider::CurseForge, "1001");
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].entry.version_name, "1.1.0");
+    assert_eq!(fs::read(archived[0].file_path()).unwrap(), b"1.1.0 payload");
+
+    let restored = roll_back(&env.game_folder, &ApiProvider::CurseForge, "1001", &mut settings).unwrap();
+    assert_eq!(restored.file_name, "BetterTrees-1.1.0.jar");
+    assert_eq!(fs::read(env.mods_dir().join("BetterTrees-1.1.0.jar")).unwrap(), b"1.1.0 payload");
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+    assert_eq!(settings.get_installed_mod("BetterTrees-1.1.0.jar").unwrap().file_id, "1.1.0");
+
+    // The version rolled back from takes its place in the archive.
+    let archived = archived_versions(&settings, &ApiProvider::CurseForge, "1001");
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].entry.version_name, "1.2.0");
+}
+
+#[test]
+fn reinstalling_the_same_file_archives_nothing() {
+    let env = TestEnv::new("archive-same");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+
+    assert!(archived_versions(&settings, &ApiProvider::CurseForge, "1001").is_empty());
+    assert!(roll_back(&env.game_folder, &ApiProvider::CurseForge, "1001", &mut settings).is_err());
+}
+
+#[test]
+fn archive_keeps_only_recent_versions() {
+    let env = TestEnv::new("archive-prune");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    for minor in 0..=MAX_ARCHIVED_VERSIONS + 1 {
+        env.install_mod(&mut settings, &format!("1.{}.0", minor)).unwrap();
+    }
+
+    let archived = archived_versions(&settings, &ApiProvider::CurseForge, "1001");
+    assert_eq!(archived.len(), MAX_ARCHIVED_VERSIONS);
+    assert!(archived.iter().all(|v| v.entry.version_name != "1.0.0"));
+}
+
+#[test]
+fn ids_that_are_not_a_single_folder_are_never_archived() {
+    let env = TestEnv::new("archive-escape");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    for version in ["1.0.0", "1.1.0"] {
+        install_mod(
+            &env.game_folder, &format!("Escape-{}.jar", version), b"payload",
+            "../escape".into(), "Escape".into(), version.into(), version.into(),
+            ApiProvider::CurseForge, ContentType::Mod, &mut settings,
+        ).unwrap();
+    }
+
+    assert!(!get_archive_dir(&settings).join("escape").exists());
+    assert!(archived_versions(&settings, &ApiProvider::CurseForge, "../escape").is_empty());
+    assert!(env.mods_dir().join("Escape-1.1.0.jar").is_file());
+}
+
+#[test]
+fn archive_lives_in_the_data_folder_unless_settings_are_custom() {
+    let env = TestEnv::new("archive-dir");
+    let settings = env.settings(ApiProvider::CurseForge);
+    assert_eq!(get_archive_dir(&settings), env.root.join("archive"));
+
+    let default_dir = get_archive_dir(&AppSettings::default());
+    assert!(default_dir.ends_with("hytale-mod-manager/archive"));
+    assert!(dirs::data_dir().is_none_or(|data| default_dir.starts_with(data)));
+}
@@ -0,0 +1,47 @@
+//! Looking up many installed mods at once.
+
+mod common;
+
+use common::server;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::get_mod_details_batch_unified;
+
+#[tokio::test]
+async fn curseforge_mods_come_from_one_request() {
+    server();
+
+    let lookups = vec![
+        (ApiProvider::CurseForge, "1001".to_string()),
+        (ApiProvider::CurseForge, "1002".to_string()),
+        (ApiProvider::CurseForge, "1001".to_string()),
+        (ApiProvider::CurseForge, "4040".to_string()),
+        (ApiProvider::CurseForge, "not-a-number".to_string()),
+        (ApiProvider::Modtale, "mt-7f3a".to_string()),
+        (ApiProvider::Modtale, "mt-91c2".to_string()),
+        (ApiProvider::Modtale, "mt-missing".to_string()),
+    ];
+    let details = get_mod_details_batch_unified(&lookups).await;
+
+    assert_eq!(details.len(), 4);
+    assert_eq!(details[&(ApiProvider::CurseForge, "1002".to_string())].name, "Hytale Minimap");
+    assert_eq!(details[&(ApiProvider::Modtale, "mt-91c2".to_string())].name, "Skyblock Tools");
+    assert!(!details.contains_key(&(ApiProvider::CurseForge, "4040".to_string())));
+    assert!(!details.contains_key(&(ApiProvider::Modtale, "mt-missing".to_string())));
+
+    let posts = server().requests_to("/curseforge/mods");
+    let posts: Vec<_> = posts.iter().filter(|r| r.method == "POST").collect();
+    assert_eq!(posts.len(), 1);
+    let body: serde_json::Value = serde_json::from_slice(&posts[0].body).unwrap();
+    assert_eq!(body["modIds"], serde_json::json!([1001, 1002, 4040]));
+
+    // Single-mod lookups are not used for CurseForge.
+    assert!(server().requests_to("/curseforge/mods/100").is_empty());
+    assert_eq!(server().requests_to("/modtale/projects/").len(), 3);
+}
+
+#[tokio::test]
+async fn empty_batch_sends_nothing() {
+    server();
+
+    assert!(get_mod_details_batch_unified(&[]).await.is_empty());
+}
@@ -0,0 +1,75 @@
+//! Category and class filtering against the recorded CurseForge category tree.
+
+mod common;
+
+use common::{server, TestEnv};
+use hytale_mod_manager::api::federated::search_mods_federated;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{get_categories_unified, search_mods_unified, SearchFilter};
+
+#[tokio::test]
+async fn curseforge_categories_are_fetched_once() {
+    server();
+
+    let categories = get_categories_unified(&ApiProvider::CurseForge).await.unwrap();
+    assert_eq!(categories.len(), 5);
+
+    let mods = categories.iter().find(|c| c.matches("mods")).unwrap();
+    assert!(mods.is_class);
+    let tools = categories.iter().find(|c| c.matches("Tools")).unwrap();
+    assert!(!tools.is_class);
+    assert_eq!(tools.class_id.as_deref(), Some("9137"));
+
+    let requests = server().requests_to("/curseforge/categories");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].query.get("gameId").map(String::as_str), Some("70216"));
+
+    get_categories_unified(&ApiProvider::CurseForge).await.unwrap();
+    assert_eq!(server().requests_to("/curseforge/categories").len(), 1);
+
+    assert!(get_categories_unified(&ApiProvider::Modtale).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn category_filter_is_sent_to_curseforge() {
+    let env = TestEnv::new("category-filter");
+    let settings = env.settings(ApiProvider::CurseForge);
+
+    let filter = SearchFilter { category_id: Some("9140".to_string()), class_id: Some("9137".to_string()) };
+    search_mods_unified(&settings, 0, "filtered".to_string(), 0, &filter).await.unwrap();
+
+    let search = server().requests_to("/curseforge/mods/search").into_iter()
+        .find(|r| r.query.get("searchFilter").map(String::as_str) == Some("filtered"))
+        .expect("search request not recorded");
+    assert_eq!(search.query.get("categoryId").map(String::as_str), Some("9140"));
+    assert_eq!(search.query.get("classId").map(String::as_str), Some("9137"));
+
+    let invalid = SearchFilter { category_id: Some("tools".to_string()), class_id: None };
+    assert!(search_mods_unified(&settings, 0, "".to_string(), 0, &invalid).await.is_err());
+}
+
+#[tokio::test]
+async fn unfiltered_search_sends_no_category() {
+    let env = TestEnv::new("category-none");
+    let settings = env.settings(ApiProvider::CurseForge);
+
+    search_mods_unified(&settings, 0, "unfiltered".to_string(), 0, &SearchFilter::default()).await.unwrap();
+
+    let search = server().requests_to("/curseforge/mods/search").into_iter()
+        .find(|r| r.query.get("searchFilter").map(String::as_str) == Some("unfiltered"))
+        .expect("search request not recorded");
+    assert!(!search.query.contains_key("categoryId"));
+    assert!(!search.query.contains_key("classId"));
+}
+
+#[tokio::test]
+async fn federated_search_skips_providers_without_categories() {
+    let env = TestEnv::new("category-federated");
+    let settings = env.settings(ApiProvider::CurseForge);
+
+    let filter = SearchFilter { category_id: None, class_id: Some("9137".to_string()) };
+    let (mods, _) = search_mods_federated(&settings, 0, "".to_string(), 0, &filter).await.unwrap();
+
+    assert!(mods.iter().all(|m| m.provider == ApiProvider::CurseForge));
+    assert!(server().requests_to("/modtale/projects").is_empty());
+}
@@ -0,0 +1,99 @@
+//! Download verification against the sizes and checksums providers publish.
+
+mod common;
+
+use common::{fixture_bytes, server, TestEnv};
+use hytale_mod_manager::api::checksum::{verify, FileChecksum, HashAlgo, Verifier};
+use hytale_mod_manager::api::local_mods::install_mod;
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{download_version_unified, get_mod_versions_unified};
+
+#[test]
+fn digests_match_reference_vectors() {
+    assert_eq!(HashAlgo::Md5.digest(b""), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(HashAlgo::Md5.digest(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(
+        HashAlgo::Md5.digest(b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"),
+        "57edf4a22be3c955ac49da2e2107b67a"
+    );
+    assert_eq!(HashAlgo::Sha1.digest(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(
+        HashAlgo::Sha256.digest(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn verify_rejects_wrong_size_and_hash() {
+    let sha1 = FileChecksum::new(HashAlgo::Sha1, " A9993E364706816ABA3E25717850C26C9CD0D89D ").unwrap();
+    assert!(verify(b"abc", Some(3), std::slice::from_ref(&sha1)).is_ok());
+    assert!(verify(b"abc", None, &[]).is_ok());
+    assert!(FileChecksum::new(HashAlgo::Md5, "  ").is_none());
+
+    let short = verify(b"ab", Some(3), &[]).unwrap_err();
+    assert!(short.contains("expected 3 bytes, got 2"), "{}", short);
+
+    let tampered = verify(b"abd", None, &[sha1]).unwrap_err();
+    assert!(tampered.starts_with("SHA-1 checksum mismatch"), "{}", tampered);
+}
+
+#[test]
+fn chunked_hashing_matches_one_shot() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let checksums: Vec<_> = [HashAlgo::Md5, HashAlgo::Sha1, HashAlgo::Sha256].iter()
+        .map(|algo| FileChecksum::new(*algo, &algo.digest(&data)).unwrap())
+        .collect();
+
+    for chunk_size in [1, 13, 63, 64, 65, 1000] {
+        let mut verifier = Verifier::new(&checksums);
+        for chunk in data.chunks(chunk_size) {
+            verifier.update(chunk);
+        }
+        assert_eq!(verifier.received(), 1000);
+        verifier.finish(Some(1000)).unwrap_or_else(|e| panic!("chunk size {}: {}", chunk_size, e));
+    }
+}
+
+#[tokio::test]
+async fn mismatched_download_leaves_installed_file_alone() {
+    let env = TestEnv::new("checksum-mismatch");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+
+    let versions = get_mod_versions_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    let old = versions.iter().find(|v| v.file_id == "5000").unwrap();
+    assert_eq!(old.checksums.len(), 2);
+
+    let (file_name, bytes) = download_version_unified(&ApiProvider::CurseForge, old).await.unwrap();
+    install_mod(
+        &env.game_folder, &file_name, &bytes,
+        "1001".into(), "Better Trees".into(), old.file_id.clone(), old.display_name.clone(),
+        ApiProvider::CurseForge, ContentType::Mod, &mut settings,
+    ).unwrap();
+
+    let mut tampered = versions.iter().find(|v| v.file_id == "5001").unwrap().clone();
+    tampered.checksums = vec![FileChecksum::new(HashAlgo::Md5, "00000000000000000000000000000000").unwrap()];
+    let err = download_version_unified(&ApiProvider::CurseForge, &tampered).await.unwrap_err();
+    assert!(err.starts_with("BetterTrees-1.2.0.jar failed verification: MD5"), "{}", err);
+
+    assert_eq!(std::fs::read(env.mods_dir().join("BetterTrees-1.1.0.jar")).unwrap(), fixture_bytes("downloads/BetterTrees-1.1.0.jar"));
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+    assert_eq!(settings.get_installed_mod("BetterTrees-1.1.0.jar").unwrap().file_id, "5000");
+}
+
+#[tokio::test]
+async fn modtale_versions_carry_checksums() {
+    server();
+
+    let versions = get_mod_versions_unified(&ApiProvider::Modtale, "mt-91c2").await.unwrap();
+    let latest = &versions[0];
+    assert_eq!(latest.file_size, Some(45));
+    assert!(latest.checksums.iter().any(|c| c.algo == HashAlgo::Sha256));
+
+    let mut truncated = latest.clone();
+    truncated.file_size = Some(44);
+    let err = download_version_unified(&ApiProvider::Modtale, &truncated).await.unwrap_err();
+    assert!(err.contains("Download is incomplete"), "{}", err);
+
+    assert!(download_version_unified(&ApiProvider::Modtale, latest).await.is_ok());
+}
@@ -0,0 +1,323 @@
+//! Runs the `hmm-cli` binary against the mock providers through `--config`.
+
+mod common;
+
+use std::process::Command;
+use common::TestEnv;
+use hytale_mod_manager::api::settings::ApiProvider;
+use serde_json::Value;
+
+fn hmm_cli(env: &TestEnv, args: &[&str]) -> (bool, Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_hmm-cli"))
+        .arg("--json")
+        .arg("--config")
+        .arg(env.root.join("settings.json"))
+        .args(args)
+        .output()
+        .expect("failed to run hmm-cli");
+
+    let document = serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!("stdout is not JSON ({}): {}", e, String::from_utf8_lossy(&output.stdout))
+    });
+    (output.status.success(), document)
+}
+
+#[test]
+fn install_list_remove_via_cli() {
+    let env = TestEnv::new("cli-flow");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, search) = hmm_cli(&env, &["search", "trees"]);
+    assert!(ok);
+    assert_eq!(search["schema_version"], 1);
+    assert_eq!(search["data"]["results"][0]["mod"]["id"], "1001");
+    assert_eq!(search["data"]["results"][0]["install"]["install_status"], "not_installed");
+
+    let (ok, install) = hmm_cli(&env, &["install", "1001", "--file", "5000"]);
+    assert!(ok, "{}", install);
+    assert_eq!(install["data"]["status"], "installed");
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+
+    let (ok, update) = hmm_cli(&env, &["update"]);
+    assert!(ok, "{}", update);
+    assert_eq!(update["data"]["results"][0]["status"], "updated");
+    assert!(env.mods_dir().join("BetterTrees-1.2.0.jar").is_file());
+
+    let (_, list) = hmm_cli(&env, &["list"]);
+    assert_eq!(list["data"]["mods"][0]["entry"]["file_id"], "5001");
+
+    let (ok, remove) = hmm_cli(&env, &["remove", "1001"]);
+    assert!(ok);
+    assert_eq!(remove["data"]["file_name"], "BetterTrees-1.2.0.jar");
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+}
+
+#[test]
+fn missing_mod_reports_structured_error() {
+    let env = TestEnv::new("cli-missing");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, info) = hmm_cli(&env, &["info", "9999"]);
+    assert!(!ok);
+    assert_eq!(info["ok"], false);
+    assert_eq!(info["error"]["code"], "not_found");
+}
+
+#[test]
+fn search_resolves_category_names() {
+    let env = TestEnv::new("cli-category");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, categories) = hmm_cli(&env, &["categories"]);
+    assert!(ok);
+    assert_eq!(categories["data"]["categories"].as_array().unwrap().len(), 5);
+
+    let (ok, search) = hmm_cli(&env, &["search", "--category", "world-generation"]);
+    assert!(ok, "{}", search);
+    assert_eq!(search["data"]["filter"]["category_id"], "9140");
+    assert_eq!(search["data"]["filter"]["class_id"], "9137");
+
+    let (ok, search) = hmm_cli(&env, &["search", "--class", "Worlds"]);
+    assert!(ok);
+    assert_eq!(search["data"]["filter"]["class_id"], "9200");
+
+    let (ok, missing) = hmm_cli(&env, &["search", "--category", "nope"]);
+    assert!(!ok);
+    assert_eq!(missing["error"]["code"], "not_found");
+}
+
+#[test]
+fn install_respects_target_game_version() {
+    let env = TestEnv::new("cli-game-version");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, config) = hmm_cli(&env, &["config", "--game-version", "0.1"]);
+    assert!(ok);
+    assert_eq!(config["data"]["target_game_version"], "0.1");
+
+    let (ok, install) = hmm_cli(&env, &["install", "1001"]);
+    assert!(ok, "{}", install);
+    assert_eq!(install["data"]["version"]["file_id"], "5000");
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+
+    // A one-off override wins over the stored target.
+    let (_, info) = hmm_cli(&env, &["info", "1001", "--target-version", "0.2"]);
+    assert_eq!(info["data"]["mod"]["version"]["file_id"], "5001");
+}
+
+#[test]
+fn parallel_download_limit_is_stored() {
+    let env = TestEnv::new("cli-parallel");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, config) = hmm_cli(&env, &["config", "--parallel-downloads", "5"]);
+    assert!(ok);
+    assert_eq!(config["data"]["max_parallel_downloads"], 5);
+
+    let (_, config) = hmm_cli(&env, &["config", "--parallel-downloads", "0"]);
+    assert_eq!(config["data"]["max_parallel_downloads"], 3);
+}
+
+#[test]
+fn install_brings_dependencies_along() {
+    let env = TestEnv::new("cli-deps");
+    env.settings(ApiProvider::CurseForge);
+
+    let (ok, plan) = hmm_cli(&env, &["install", "1004", "--dry-run"]);
+    assert!(ok, "{}", plan);
+    assert_eq!(plan["data"]["installs"][0]["mod"]["id"], "1004");
+    assert_eq!(plan["data"]["missing"][0]["mod_id"], "4040");
+    assert!(!env.mods_dir().join("ForestBiomes-1.0.0.jar").exists());
+
+    let (ok, install) = hmm_cli(&env, &["install", "1004"]);
+    assert!(ok, "{}", install);
+    let dependencies: Vec<_> = install["data"]["dependencies"].as_array().unwrap().iter()
+        .map(|d| d["mod_id"].as_str().unwrap())
+        .collect();
+    assert!(dependencies.contains(&"1005") && dependencies.contains(&"1006"), "{:?}", dependencies);
+    assert!(env.mods_dir().join("HytaleLib-2.1.0.jar").is_file());
+
+    let (ok, install) = hmm_cli(&env, &["install", "1004", "--no-deps"]);
+    assert!(ok, "{}", install);
+    assert!(install["data"].get("dependencies").is_none());
+}
+
+#[test]
+fn remove_protects_dependencies() {
+    let env = TestEnv::new("cli-remove-deps");
+    env.settings(ApiProvider::CurseForge);
+    let (ok, install) = hmm_cli(&env, &["install", "1004"]);
+    assert!(ok, "{}", install);
+
+    let (ok, remove) = hmm_cli(&env, &["remove", "1005"]);
+    assert!(!ok);
+    assert_eq!(remove["error"]["code"], "has_dependents");
+    assert!(env.mods_dir().join("HytaleLib-2.1.0.jar").is_file());
+
+    let (_, orphans) = hmm_cli(&env, &["orphans"]);
+    assert_eq!(orphans["data"]["mods"].as_array().unwrap().len(), 0);
+
+    let (ok, remove) = hmm_cli(&env, &["remove", "1004"]);
+    assert!(ok, "{}", remove);
+    let (ok, orphans) = hmm_cli(&env, &["orphans", "--remove"]);
+    assert!(ok, "{}", orphans);
+    assert_eq!(orphans["data"]["mods"].as_array().unwrap().len(), 3);
+    assert!(!env.mods_dir().join("HytaleLib-2.1.0.jar").exists());
+}
+
+#[test]
+fn install_refuses_incompatible_mods_without_force() {
+    let env = TestEnv::new("cli-conflicts");
+    env.settings(ApiProvider::CurseForge);
+    let (ok, install) = hmm_cli(&env, &["install", "1001"]);
+    assert!(ok, "{}", install);
+
+    let (ok, install) = hmm_cli(&env, &["install", "1004"]);
+    assert!(!ok);
+    assert_eq!(install["error"]["code"], "incompatible");
+    assert!(!env.mods_dir().join("ForestBiomes-1.0.0.jar").exists());
+
+    let (ok, install) = hmm_cli(&env, &["install", "1004", "--force"]);
+    assert!(ok, "{}", install);
+    assert_eq!(install["data"]["conflicts"][0]["other_mod_id"], "1001");
+
+    let (ok, audit) = hmm_cli(&env, &["audit"]);
+    assert!(ok);
+    assert_eq!(audit["data"]["conflicts"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn rollback_restores_the_replaced_version() {
+    let env = TestEnv::new("cli-rollback");
+    env.settings(ApiProvider::CurseForge);
+    assert!(hmm_cli(&env, &["install", "1001", "--file", "5000"]).0);
+    assert!(hmm_cli(&env, &["update"]).0);
+
+    let (_, list) = hmm_cli(&env, &["rollback", "1001", "--list"]);
+    assert_eq!(list["data"]["archived"][0]["entry"]["file_id"], "5000");
+
+    let (ok, rollback) = hmm_cli(&env, &["rollback", "1001"]);
+    assert!(ok, "{}", rollback);
+    assert_eq!(rollback["data"]["restored"]["file_name"], "BetterTrees-1.1.0.jar");
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+}
+
+#[test]
+fn disable_and_enable_via_cli() {
+    let env = TestEnv::new("cli-toggle");
+    env.settings(ApiProvider::CurseForge);
+    assert!(hmm_cli(&env, &["install", "1001", "--file", "5000"]).0);
+
+    let (ok, disable) = hmm_cli(&env, &["disable", "1001"]);
+    assert!(ok, "{}", disable);
+    assert_eq!(disable["data"]["enabled"], false);
+    assert!(!env.mods_dir().join("BetterTrees-1.1.0.jar").exists());
+
+    let (_, list) = hmm_cli(&env, &["list"]);
+    assert_eq!(list["data"]["mods"][0]["entry"]["disabled"], true);
+
+    let (ok, _) = hmm_cli(&env, &["enable", "BetterTrees-1.1.0.jar"]);
+    assert!(ok);
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+}
+
+#[test]
+fn manual_downloads_are_imported_via_cli() {
+    let env = TestEnv::new("cli-manual");
+    env.settings(ApiProvider::CurseForge);
+    let downloads = env.root.join("Downloads");
+    std::fs::create_dir_all(&downloads).unwrap();
+
+    let (ok, install) = hmm_cli(&env, &["install", "1007"]);
+    assert!(!ok);
+    assert_eq!(install["error"]["code"], "manual_download_required");
+
+    let (ok, import) = hmm_cli(&env, &["import", "1007", "--from", downloads.to_str().unwrap()]);
+    assert!(!ok);
+    assert_eq!(import["error"]["code"], "not_found");
+
+    std::fs::write(downloads.join("SkyIslands-1.0.0.jar"), b"SkyIslands-1.0.0.jar test payload\n").unwrap();
+    let (ok, import) = hmm_cli(&env, &["import", "1007", "--from", downloads.to_str().unwrap()]);
+    assert!(ok, "{}", import);
+    assert_eq!(import["data"]["version"]["file_id"], "6701");
+    assert!(env.mods_dir().join("SkyIslands-1.0.0.jar").is_file());
+}
+
+#[test]
+fn routes_are_configured_via_cli() {
+    let env = TestEnv::new("cli-routes");
+    env.settings(ApiProvider::CurseForge);
+    assert!(hmm_cli(&env, &["install", "1008"]).0);
+    assert!(env.game_folder.join("UserData/Saves/SkyCastle-1.0.0.zip").is_file());
+
+    let (ok, config) = hmm_cli(&env, &["config", "--route", "world=UserData/Worlds", "--route-class", "9300=prefab"]);
+    assert!(ok, "{}", config);
+    assert_eq!(config["data"]["routes"]["curseforge_classes"]["9300"], "prefab");
+    assert_eq!(config["data"]["routes"]["folders"]["asset-pack"], "UserData/Packs");
+    assert!(env.game_folder.join("UserData/Worlds/SkyCastle-1.0.0.zip").is_file());
+
+    let (_, list) = hmm_cli(&env, &["list"]);
+    let content_type = list["data"]["mods"][0]["entry"]["content_type"].as_str().unwrap();
+    assert_eq!(content_type, "world");
+
+    // A route given with a new game folder applies to the new folder only.
+    let other = env.root.join("OtherGame");
+    let route = format!("{}=UserData/Saves", content_type);
+    let (ok, config) = hmm_cli(&env, &["config", "--game-folder", other.to_str().unwrap(), "--route", &route]);
+    assert!(ok, "{}", config);
+    assert!(env.game_folder.join("UserData/Worlds/SkyCastle-1.0.0.zip").is_file());
+}
+
+#[test]
+fn local_files_are_installed_via_cli() {
+    let env = TestEnv::new("cli-install-file");
+    env.settings(ApiProvider::CurseForge);
+    let path = env.root.join("FriendsMod-0.9.0.jar");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    zip.start_file("manifest.json", zip::write::SimpleFileOptions::default()).unwrap();
+    std::io::Write::write_all(&mut zip, br#"{"Group": "net.friend", "Name": "FriendsMod", "Version": "0.9.0"}"#).unwrap();
+    zip.finish().unwrap();
+
+    let (ok, installed) = hmm_cli(&env, &["install-file", path.to_str().unwrap()]);
+    assert!(ok, "{}", installed);
+    assert_eq!(installed["data"]["mod_id"], "net.friend.FriendsMod");
+    assert_eq!(installed["data"]["content_type"], "mod");
+    assert!(env.mods_dir().join("FriendsMod-0.9.0.jar").is_file());
+
+    let (_, list) = hmm_cli(&env, &["list"]);
+    assert_eq!(list["data"]["mods"][0]["entry"]["api_provider"], "Local");
+
+    let (ok, missing) = hmm_cli(&env, &["install-file", env.root.join("missing.jar").to_str().unwrap()]);
+    assert!(!ok);
+    assert_eq!(missing["error"]["code"], "not_found");
+}
+
+#[test]
+fn a_stored_key_that_is_not_a_valid_header_can_be_replaced() {
+    let env = TestEnv::new("cli-bad-key");
+    env.settings(ApiProvider::CurseForge);
+    let path = env.root.join("settings.json");
+    let mut document: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    document["api_keys"]["CurseForge"] = "test-key\n".into();
+    std::fs::write(&path, serde_json::to_string(&document).unwrap()).unwrap();
+
+    let (ok, config) = hmm_cli(&env, &["config", "--api-key", "test-key"]);
+    assert!(ok, "{}", config);
+    assert!(hmm_cli(&env, &["search", "trees"]).0);
+}
+
+#[test]
+fn a_failed_target_still_reports_its_installed_dependencies() {
+    let env = TestEnv::new("cli-target-fails");
+    env.settings(ApiProvider::CurseForge);
+    // A folder in the way makes moving the target's file into place fail.
+    std::fs::create_dir_all(env.mods_dir().join("ForestBiomes-1.0.0.jar")).unwrap();
+
+    let (ok, install) = hmm_cli(&env, &["install", "1004"]);
+    assert!(!ok);
+    assert_eq!(install["error"]["code"], "install_failed");
+    assert_eq!(install["data"]["status"], "failed");
+    assert_eq!(install["data"]["dependencies"][0]["status"], "installed");
+    assert!(env.mods_dir().join("HytaleLib-2.1.0.jar").is_file());
+}
@@ -0,0 +1,352 @@
+//! In-process HTTP stand-in for the CurseForge and Modtale APIs.
+//!
+//! Requests are answered from `tests/fixtures`, mirroring the URL path:
+//! `GET /curseforge/mods/1001` serves `fixtures/curseforge/mods/1001.json`,
+//! and a path naming an existing file (such as a download) serves it as-is.
+//! A fixture named `<path>@<key>=<value>.json` wins over `<path>.json` when
+//! the request carries that query parameter, which is how pages are served.
+//! `{{BASE}}` inside JSON fixtures is replaced with the server's address.
+//! Raw files honour `Range: bytes=<start>-` unless told otherwise, and a
+//! download can be made to break off part-way with [`MockServer::truncate_next`].
+//! [`MockServer::fail_next`] queues error responses ahead of the real one.
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use hytale_mod_manager::api::local_mods;
+use hytale_mod_manager::api::provider::{registry, BaseUrls};
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Misbehaviour to inject, keyed by request path.
+#[derive(Default)]
+struct Faults {
+    /// Send the full `Content-Length` but only this many bytes, then hang up.
+    truncate: HashMap<String, usize>,
+    /// Answer range requests with the whole file, like a CDN without range support.
+    ignore_range: Vec<String>,
+    /// Error responses to send before the fixture, with their `Retry-After`.
+    failures: HashMap<String, VecDeque<(&'static str, Option<u64>)>>,
+}
+
+pub struct MockServer {
+    pub base: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    faults: Arc<Mutex<Faults>>,
+}
+
+impl MockServer {
+    pub fn curseforge_urls(&self) -> BaseUrls {
+        BaseUrls { api: Some(format!("{}/curseforge", self.base)), cdn: None }
+    }
+
+    pub fn modtale_urls(&self) -> BaseUrls {
+        BaseUrls {
+            api: Some(format!("{}/modtale", self.base)),
+            cdn: Some(format!("{}/modtale-cdn", self.base)),
+        }
+    }
+
+    /// Every request received so far whose path starts with `prefix`.
+    pub fn requests_to(&self, prefix: &str) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap()
+            .iter()
+            .filter(|r| r.path.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// Cuts the next response to `path` off after `bytes` bytes of body.
+    pub fn truncate_next(&self, path: &str, bytes: usize) {
+        self.faults.lock().unwrap().truncate.insert(path.to_string(), bytes);
+    }
+
+    /// Answers the next request to `path` with `status` instead of the
+    /// fixture. Calls queue up, one failure per request.
+    pub fn fail_next(&self, path: &str, status: &'static str, retry_after: Option<u64>) {
+        self.faults.lock().unwrap()
+            .failures
+            .entry(path.to_string())
+            .or_default()
+            .push_back((status, retry_after));
+    }
+
+    /// Makes `path` ignore `Range` headers from now on.
+    pub fn ignore_range(&self, path: &str) {
+        self.faults.lock().unwrap().ignore_range.push(path.to_string());
+    }
+}
+
+static SERVER: OnceLock<MockServer> = OnceLock::new();
+
+/// Starts the shared mock server once per test binary and points every
+/// registered provider at it.
+pub fn server() -> &'static MockServer {
+    SERVER.get_or_init(|| {
+        let std_listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        std_listener.set_nonblocking(true).unwrap();
+        let base = format!("http://{}", std_listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let faults = Arc::new(Mutex::new(Faults::default()));
+
+        let thread_base = base.clone();
+        let thread_requests = requests.clone();
+        let thread_faults = faults.clone();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(async move {
+                let listener = TcpListener::from_std(std_listener).unwrap();
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else { continue };
+                    let base = thread_base.clone();
+                    let requests = thread_requests.clone();
+                    let faults = thread_faults.clone();
+                    tokio::spawn(async move {
+                        let _ = handle(stream, &base, &requests, &faults).await;
+                    });
+                }
+            });
+        });
+
+        let server = MockServer { base, requests, faults };
+        if let Some(p) = registry().get(&ApiProvider::CurseForge) {
+            p.set_base_urls(&server.curseforge_urls());
+            p.set_api_key("test-key");
+        }
+        if let Some(p) = registry().get(&ApiProvider::Modtale) {
+            p.set_base_urls(&server.modtale_urls());
+        }
+        server
+    })
+}
+
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+pub fn fixture_bytes(relative: &str) -> Vec<u8> {
+    std::fs::read(fixtures_dir().join(relative)).expect("missing fixture")
+}
+
+/// A scratch directory holding a game folder and a settings file.
+pub struct TestEnv {
+    pub root: PathBuf,
+    pub game_folder: PathBuf,
+}
+
+impl TestEnv {
+    pub fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("hmm-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let game_folder = root.join("hytale");
+        std::fs::create_dir_all(&game_folder).unwrap();
+        Self { root, game_folder }
+    }
+
+    /// Writes a settings file pointing at the mock server and loads it, so
+    /// loading re-applies the mock URLs rather than the built-in defaults.
+    pub fn settings(&self, provider: ApiProvider) -> AppSettings {
+        let server = server();
+        let path = self.root.join("settings.json");
+        let document = serde_json::json!({
+            "game_folder": self.game_folder,
+            "theme": "Dark",
+            "api_provider": provider.clone(),
+            "api_keys": { "CurseForge": "test-key" },
+            "base_urls": {
+                "CurseForge": server.curseforge_urls(),
+                "Modtale": server.modtale_urls(),
+            },
+            "installed_mods": {},
+        });
+        std::fs::write(&path, serde_json::to_string_pretty(&document).unwrap()).unwrap();
+
+        let settings = AppSettings::load_from(path);
+        assert_eq!(settings.get_api_provider(), provider, "test settings failed to load");
+        settings
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.game_folder.join("UserData").join("Mods")
+    }
+
+    /// Installs `BetterTrees-<version>.jar` holding `<version> payload`
+    /// without going through a provider, with `version` as its file id.
+    pub fn install_mod(&self, settings: &mut AppSettings, version: &str) -> Result<(), String> {
+        local_mods::install_mod(
+            &self.game_folder, &format!("BetterTrees-{}.jar", version), format!("{} payload", version).as_bytes(),
+            "1001".into(), "Better Trees".into(), version.into(), version.into(),
+            ApiProvider::CurseForge, ContentType::Mod, settings,
+        )
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    base: &str,
+    requests: &Mutex<Vec<RecordedRequest>>,
+    faults: &Mutex<Faults>,
+) -> std::io::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|l| l.split_once(':'))
+        .map(|(k, v)| (k.trim().to_lowercase(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers.get("content-length").and_then(|v| v.parse::<usize>().ok()).unwrap_or(0);
+    let mut body = buf[header_end..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+
+    let (path, query_string) = target.split_once('?').unwrap_or((target.as_str(), ""));
+    let query = query_string.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.to_string(), decode(v)))
+        .collect();
+
+    requests.lock().unwrap().push(RecordedRequest {
+        method: method.clone(),
+        path: path.to_string(),
+        query,
+        headers: headers.clone(),
+        body,
+    });
+
+    let (failure, truncate, ignore_range) = {
+        let mut faults = faults.lock().unwrap();
+        let failure = faults.failures.get_mut(path).and_then(|queue| queue.pop_front());
+        (failure, faults.truncate.remove(path), faults.ignore_range.iter().any(|p| p == path))
+    };
+
+    let (mut status, content_type, mut payload) = match failure {
+        Some((status, _)) => (status, "application/json", br#"{"error":"injected"}"#.to_vec()),
+        None => respond(path, query_string, base),
+    };
+    let mut extra_headers = String::new();
+    if let Some((_, Some(seconds))) = failure {
+        extra_headers.push_str(&format!("Retry-After: {}\r\n", seconds));
+    }
+
+    let range_start = headers.get("range")
+        .and_then(|r| r.strip_prefix("bytes="))
+        .and_then(|r| r.strip_suffix('-'))
+        .and_then(|r| r.parse::<usize>().ok());
+    if let Some(start) = range_start.filter(|_| content_type == "application/octet-stream" && !ignore_range) {
+        let total = payload.len();
+        if start >= total {
+            status = "416 Range Not Satisfiable";
+            payload.clear();
+        } else {
+            status = "206 Partial Content";
+            extra_headers += &format!("Content-Range: bytes {}-{}/{}\r\n", start, total - 1, total);
+            payload.drain(..start);
+        }
+    }
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        status,
+        content_type,
+        payload.len(),
+        extra_headers
+    ).into_bytes();
+    if let Some(bytes) = truncate {
+        payload.truncate(bytes);
+    }
+    response.extend_from_slice(&payload);
+    stream.write_all(&response).await?;
+    if truncate.is_some() {
+        // Let the client read what was sent before the connection drops.
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    stream.shutdown().await
+}
+
+fn respond(path: &str, query_string: &str, base: &str) -> (&'static str, &'static str, Vec<u8>) {
+    let relative = path.trim_start_matches('/');
+    if relative.split('/').any(|segment| segment == "..") || query_string.contains('/') {
+        return ("400 Bad Request", "text/plain", b"bad path".to_vec());
+    }
+
+    let raw = fixtures_dir().join(relative);
+    if raw.is_file() {
+        return ("200 OK", "application/octet-stream", std::fs::read(raw).unwrap());
+    }
+
+    let json = query_string.split('&')
+        .filter(|pair| pair.contains('='))
+        .map(|pair| fixtures_dir().join(format!("{}@{}.json", relative, pair)))
+        .find(|candidate| candidate.is_file())
+        .unwrap_or_else(|| fixtures_dir().join(format!("{}.json", relative)));
+    match std::fs::read_to_string(json) {
+        Ok(text) => ("200 OK", "application/json", text.replace("{{BASE}}", base).into_bytes()),
+        Err(_) => ("404 Not Found", "application/json", br#"{"error":"not found"}"#.to_vec()),
+    }
+}
+
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 3 <= bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,180 @@
+//! Resolving and installing the mods another mod requires.
+
+mod common;
+
+use std::cell::RefCell;
+use common::TestEnv;
+use futures::future::join_all;
+use hytale_mod_manager::api::dependencies::{audit_conflicts, resolve_install_plan, InstallPlan};
+use hytale_mod_manager::api::local_mods::{find_dependents, find_orphans, remove_mod, remove_mod_with_dependents, set_mod_enabled};
+use hytale_mod_manager::api::queue::{install_job, InstallQueue, JobId, JobState};
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+use hytale_mod_manager::api::ui_mod::{get_mod_details_unified, DependencyKind, UiMod};
+
+async fn details(id: &str) -> UiMod {
+    get_mod_details_unified(&ApiProvider::CurseForge, id).await.unwrap()
+}
+
+/// Installs Forest Biomes and everything it requires, one at a time.
+async fn install_forest_biomes(env: &TestEnv) -> AppSettings {
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(1);
+    let biomes = details("1004").await;
+    let plan = resolve_install_plan(&biomes, biomes.version.clone(), &settings).await;
+    for id in plan.enqueue(&queue) {
+        queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, &mut settings)).await;
+    }
+    settings
+}
+
+fn ids<'a>(items: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    items.into_iter().collect()
+}
+
+#[tokio::test]
+async fn plan_walks_required_dependencies() {
+    let env = TestEnv::new("deps-plan");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+
+    let minimap = details("1002").await;
+    let queue = InstallQueue::new(1);
+    let id = queue.enqueue(&minimap, minimap.version.clone());
+    queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, &mut settings)).await;
+
+    let biomes = details("1004").await;
+    assert_eq!(biomes.version.dependencies[2].kind, DependencyKind::Optional);
+    assert_eq!(biomes.version.required_mod_ids(), vec!["1005", "1002"]);
+
+    let plan = resolve_install_plan(&biomes, biomes.version.clone(), &settings).await;
+
+    assert_eq!(ids(plan.installs.iter().map(|p| p.ui_mod.id.as_str())), vec!["1004", "1005", "1006"]);
+    assert_eq!(plan.target().required_by, None);
+    assert_eq!(plan.dependencies()[1].required_by.as_deref(), Some("Hytale Lib"));
+    assert_eq!(ids(plan.satisfied.iter().map(|s| s.mod_id.as_str())), vec!["1002"]);
+    assert_eq!(plan.satisfied[0].required_by, "Forest Biomes");
+    assert_eq!(ids(plan.missing.iter().map(|m| m.mod_id.as_str())), vec!["4040"]);
+    assert!(plan.has_dependencies());
+
+    // A disabled mod isn't loaded by the game, so it doesn't count.
+    set_mod_enabled(&env.game_folder, "HytaleMinimap-0.4.1.jar", false, &mut settings).unwrap();
+    let plan = resolve_install_plan(&biomes, biomes.version.clone(), &settings).await;
+    assert!(plan.satisfied.is_empty());
+    let disabled = plan.missing.iter().find(|m| m.mod_id == "1002").unwrap();
+    assert_eq!(disabled.reason, "Hytale Minimap is installed but disabled");
+}
+
+#[tokio::test]
+async fn dependencies_install_and_are_recorded() {
+    let env = TestEnv::new("deps-install");
+    let settings = RefCell::new(env.settings(ApiProvider::CurseForge));
+    let queue = InstallQueue::new(2);
+
+    let biomes = details("1004").await;
+    let snapshot = settings.borrow().clone();
+    let plan = resolve_install_plan(&biomes, biomes.version.clone(), &snapshot).await;
+    let jobs = plan.enqueue(&queue);
+    assert_eq!(jobs.len(), 4);
+    assert_eq!(queue.job(jobs[3]).unwrap().required_by.as_deref(), Some("Hytale Lib"));
+
+    let states = join_all(jobs.iter().map(|id| {
+        let (queue, settings, env) = (&queue, &settings, &env);
+        queue.run(*id, &env.game_folder, move |job, file| install_job(&env.game_folder, job, file, &mut settings.borrow_mut()))
+    })).await;
+    assert!(states.iter().all(|s| *s == JobState::Done), "{:?}", states);
+
+    for file in ["ForestBiomes-1.0.0.jar", "HytaleLib-2.1.0.jar", "CoreApi-1.0.0.jar", "HytaleMinimap-0.4.1.jar"] {
+        assert!(env.mods_dir().join(file).is_file(), "{} missing", file);
+    }
+
+    let settings = settings.into_inner();
+    let target = settings.get_installed_mod("ForestBiomes-1.0.0.jar").unwrap();
+    assert!(!target.is_dependency);
+    assert_eq!(target.dependencies, vec!["1005", "1002"]);
+    let library = settings.get_installed_mod("HytaleLib-2.1.0.jar").unwrap();
+    assert!(library.is_dependency);
+    assert_eq!(library.dependencies, vec!["1006"]);
+}
+
+async fn run(env: &TestEnv, queue: &InstallQueue, id: JobId, settings: &mut AppSettings) {
+    let state = queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, settings)).await;
+    assert_eq!(state, JobState::Done);
+}
+
+#[tokio::test]
+async fn updates_keep_a_dependency_marked_and_explicit_installs_clear_it() {
+    let env = TestEnv::new("deps-reinstall");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(1);
+    let library = details("1005").await;
+
+    run(&env, &queue, queue.enqueue_dependency(&library, library.version.clone(), "Forest Biomes"), &mut settings).await;
+    run(&env, &queue, queue.enqueue_update(&library, library.version.clone()), &mut settings).await;
+    assert!(settings.get_installed_mod("HytaleLib-2.1.0.jar").unwrap().is_dependency);
+
+    run(&env, &queue, queue.enqueue(&library, library.version.clone()), &mut settings).await;
+    assert!(!settings.get_installed_mod("HytaleLib-2.1.0.jar").unwrap().is_dependency);
+    assert!(find_orphans(&settings).is_empty());
+
+    // Another mod needing it later doesn't take it back from the user.
+    run(&env, &queue, queue.enqueue_dependency(&library, library.version.clone(), "Forest Biomes"), &mut settings).await;
+    assert!(!settings.get_installed_mod("HytaleLib-2.1.0.jar").unwrap().is_dependency);
+    assert_eq!(settings.installed_mods.len(), 1);
+}
+
+#[tokio::test]
+async fn dependents_and_orphans_follow_the_manifest() {
+    let env = TestEnv::new("deps-remove");
+    let mut settings = install_forest_biomes(&env).await;
+
+    assert_eq!(find_dependents(&settings, "HytaleLib-2.1.0.jar"), vec!["CoreApi-1.0.0.jar", "ForestBiomes-1.0.0.jar"]);
+    assert_eq!(find_dependents(&settings, "HytaleMinimap-0.4.1.jar"), vec!["ForestBiomes-1.0.0.jar"]);
+    assert!(find_dependents(&settings, "ForestBiomes-1.0.0.jar").is_empty());
+    assert!(find_orphans(&settings).is_empty());
+
+    remove_mod(&env.game_folder, "ForestBiomes-1.0.0.jar", &mut settings).unwrap();
+    assert_eq!(find_orphans(&settings), vec!["CoreApi-1.0.0.jar", "HytaleLib-2.1.0.jar", "HytaleMinimap-0.4.1.jar"]);
+}
+
+#[tokio::test]
+async fn removing_with_dependents_takes_them_along() {
+    let env = TestEnv::new("deps-cascade");
+    let mut settings = install_forest_biomes(&env).await;
+
+    let removed = remove_mod_with_dependents(&env.game_folder, "HytaleMinimap-0.4.1.jar", &mut settings).unwrap();
+
+    assert_eq!(removed, vec!["ForestBiomes-1.0.0.jar"]);
+    assert!(!env.mods_dir().join("ForestBiomes-1.0.0.jar").exists());
+    assert!(!env.mods_dir().join("HytaleMinimap-0.4.1.jar").exists());
+    assert!(env.mods_dir().join("HytaleLib-2.1.0.jar").is_file());
+}
+
+#[tokio::test]
+async fn incompatible_mods_are_flagged_both_ways() {
+    let env = TestEnv::new("deps-conflicts");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(1);
+    let trees = details("1001").await;
+    let id = queue.enqueue(&trees, trees.version.clone());
+    queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, &mut settings)).await;
+
+    // Core API, pulled in by Forest Biomes, declares Better Trees incompatible.
+    let biomes = details("1004").await;
+    let plan = resolve_install_plan(&biomes, biomes.version.clone(), &settings).await;
+    assert_eq!(plan.conflicts.len(), 1);
+    assert_eq!((plan.conflicts[0].mod_id.as_str(), plan.conflicts[0].other_mod_id.as_str()), ("1006", "1001"));
+    assert!(plan.needs_review());
+
+    for id in plan.enqueue(&queue) {
+        queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, &mut settings)).await;
+    }
+    let audit = audit_conflicts(&settings);
+    assert_eq!(audit.len(), 1);
+    assert_eq!((audit[0].mod_name.as_str(), audit[0].other_mod_name.as_str()), ("Better Trees", "Core API"));
+
+    // The installed side's record catches it when Better Trees comes back.
+    let file_name = settings.installed_mods.iter().find(|(_, e)| e.mod_id == "1001").unwrap().0.clone();
+    remove_mod(&env.game_folder, &file_name, &mut settings).unwrap();
+    let mut plan = InstallPlan::single(&trees, trees.version.clone());
+    plan.check_conflicts(&settings);
+    assert_eq!(plan.conflicts[0].other_mod_name, "Core API");
+}
@@ -0,0 +1,69 @@
+//! Streaming downloads into the staging folder beside `Mods`.
+
+mod common;
+
+use common::{fixture_bytes, TestEnv};
+use hytale_mod_manager::api::checksum::{FileChecksum, HashAlgo};
+use hytale_mod_manager::api::download::{format_bytes, get_downloads_dir, progress_channel, DownloadProgress};
+use hytale_mod_manager::api::local_mods::install_downloaded_mod;
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{download_version_to_file_unified, get_mod_versions_unified};
+
+#[tokio::test]
+async fn streams_to_staging_then_moves_into_mods() {
+    let env = TestEnv::new("download-stream");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+
+    let versions = get_mod_versions_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    let latest = versions.iter().find(|v| v.file_id == "5001").unwrap();
+
+    let (tx, rx) = progress_channel();
+    let download = download_version_to_file_unified(&ApiProvider::CurseForge, latest, &env.game_folder, Some(&tx))
+        .await
+        .unwrap();
+
+    assert_eq!(*rx.borrow(), DownloadProgress { received: 34, total: Some(34) });
+    assert_eq!(download.size, 34);
+    assert!(download.path.starts_with(get_downloads_dir(&env.game_folder)));
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+
+    let staged = download.path.clone();
+    install_downloaded_mod(
+        &env.game_folder, download,
+        "1001".into(), "Better Trees".into(), latest.file_id.clone(), latest.display_name.clone(),
+        ApiProvider::CurseForge, ContentType::Mod, &mut settings,
+    ).unwrap();
+
+    assert!(!staged.exists());
+    assert_eq!(std::fs::read(env.mods_dir().join("BetterTrees-1.2.0.jar")).unwrap(), fixture_bytes("downloads/BetterTrees-1.2.0.jar"));
+    assert_eq!(settings.get_installed_mod("BetterTrees-1.2.0.jar").unwrap().file_id, "5001");
+}
+
+#[tokio::test]
+async fn failed_verification_discards_partial_file() {
+    let env = TestEnv::new("download-mismatch");
+    env.settings(ApiProvider::Modtale);
+
+    let versions = get_mod_versions_unified(&ApiProvider::Modtale, "mt-91c2").await.unwrap();
+    let mut tampered = versions[0].clone();
+    tampered.checksums = vec![FileChecksum::new(HashAlgo::Sha1, &"0".repeat(40)).unwrap()];
+
+    let err = download_version_to_file_unified(&ApiProvider::Modtale, &tampered, &env.game_folder, None)
+        .await
+        .unwrap_err();
+    assert!(err.contains("SHA-1 checksum mismatch"), "{}", err);
+
+    let leftovers = std::fs::read_dir(get_downloads_dir(&env.game_folder)).unwrap().count();
+    assert_eq!(leftovers, 0);
+    assert!(!env.mods_dir().exists());
+}
+
+#[test]
+fn progress_formatting() {
+    assert_eq!(DownloadProgress { received: 5, total: Some(20) }.fraction(), Some(0.25));
+    assert_eq!(DownloadProgress { received: 5, total: None }.fraction(), None);
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KB");
+    assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MB");
+}
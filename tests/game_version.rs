@@ -0,0 +1,96 @@
+//! Target game version handling. The target is applied to every provider at
+//! once, so the flows that change it run in this binary one after another.
+
+mod common;
+
+use common::{server, TestEnv};
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{
+    get_mod_details_batch_unified, get_mod_details_unified, pick_version, search_mods_unified, supports_game_version, SearchFilter, UiModVersion,
+};
+
+fn version(id: &str, release_type: u8, date: &str, game_versions: &[&str]) -> UiModVersion {
+    UiModVersion {
+        file_id: id.to_string(),
+        display_name: id.to_string(),
+        file_name: format!("{}.jar", id),
+        download_url: None,
+        release_type,
+        game_versions: game_versions.iter().map(|v| v.to_string()).collect(),
+        upload_date: date.to_string(),
+        file_size: None,
+        checksums: vec![],
+        dependencies: vec![],
+    }
+}
+
+#[test]
+fn game_version_matching() {
+    let versions = vec!["0.2.1".to_string(), "V0.3".to_string()];
+    assert!(supports_game_version(&versions, "0.2.1"));
+    assert!(supports_game_version(&versions, "0.2"));
+    assert!(supports_game_version(&versions, "0.3"));
+    assert!(!supports_game_version(&versions, "0.2.10"));
+    assert!(!supports_game_version(&["0.20".to_string()], "0.2"));
+    assert!(!supports_game_version(&[], "0.2"));
+    assert!(supports_game_version(&[], " "));
+}
+
+#[test]
+fn picks_newest_compatible_release() {
+    let files = vec![
+        version("beta-new", 2, "2026-03-01", &["0.1.0"]),
+        version("release-old", 1, "2026-01-01", &["0.1.0"]),
+        version("release-mid", 1, "2026-02-01", &["0.1.0"]),
+        version("release-newer-client", 1, "2026-04-01", &["0.2.0"]),
+    ];
+
+    assert_eq!(pick_version(files.clone(), Some("0.1")).unwrap().file_id, "release-mid");
+    assert_eq!(pick_version(files.clone(), None).unwrap().file_id, "release-newer-client");
+    assert!(pick_version(files.clone(), Some("0.3")).is_none());
+
+    let betas = vec![version("beta-a", 2, "2026-01-01", &["0.1"]), version("beta-b", 3, "2026-02-01", &["0.1"])];
+    assert_eq!(pick_version(betas, Some("0.1")).unwrap().file_id, "beta-b");
+}
+
+#[tokio::test]
+async fn target_version_steers_search_and_details() {
+    let env = TestEnv::new("game-version");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    settings.set_target_game_version(Some(" 0.1 ".to_string()));
+    assert_eq!(settings.get_target_game_version().as_deref(), Some("0.1"));
+
+    search_mods_unified(&settings, 0, "versioned".to_string(), 0, &SearchFilter::default()).await.unwrap();
+    let search = server().requests_to("/curseforge/mods/search").into_iter()
+        .find(|r| r.query.get("searchFilter").map(String::as_str) == Some("versioned"))
+        .expect("search request not recorded");
+    assert_eq!(search.query.get("gameVersion").map(String::as_str), Some("0.1"));
+
+    // Only 1.2.0 is among the latest files and it needs 0.2, so details fall
+    // back to the full file list.
+    let trees = get_mod_details_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    assert_eq!(trees.version.file_id, "5000");
+
+    let minimap = get_mod_details_unified(&ApiProvider::CurseForge, "1002").await.unwrap();
+    assert!(minimap.version.file_id.is_empty());
+
+    let sky = get_mod_details_unified(&ApiProvider::Modtale, "mt-91c2").await.unwrap();
+    assert_eq!(sky.version.display_name, "1.9.0");
+
+    // A failed file lookup keeps the mod in the batch, just without a version.
+    server().fail_next("/curseforge/mods/1001/files", "404 Not Found", None);
+    let lookups = vec![(ApiProvider::CurseForge, "1001".to_string()), (ApiProvider::CurseForge, "1002".to_string())];
+    let details = get_mod_details_batch_unified(&lookups).await;
+    assert_eq!(details.len(), 2);
+    assert!(details[&(ApiProvider::CurseForge, "1001".to_string())].version.file_id.is_empty());
+
+    settings.set_api_provider(ApiProvider::Modtale);
+    let (mods, _) = search_mods_unified(&settings, 0, "".to_string(), 0, &SearchFilter::default()).await.unwrap();
+    let ids: Vec<_> = mods.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["mt-91c2"]);
+
+    settings.set_target_game_version(Some("".to_string()));
+    assert_eq!(settings.get_target_game_version(), None);
+    let sky = get_mod_details_unified(&ApiProvider::Modtale, "mt-91c2").await.unwrap();
+    assert_eq!(sky.version.display_name, "2.0.0");
+}
@@ -0,0 +1,127 @@
+//! Installing jars and zips the user already has, outside any provider.
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use common::TestEnv;
+use hytale_mod_manager::api::archive::archived_versions;
+use hytale_mod_manager::api::local_file::{install_from_file, read_local_mod};
+use hytale_mod_manager::api::local_mods::find_dependents;
+use hytale_mod_manager::api::routing::ContentType;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+
+/// Writes an archive holding `files` into a scratch folder next to the game.
+fn archive(env: &TestEnv, name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env.root.join("Builds");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+    for (file_name, contents) in files {
+        zip.start_file(*file_name, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+    path
+}
+
+fn plugin(env: &TestEnv, name: &str, version: &str) -> PathBuf {
+    let manifest = format!(r#"{{
+        "Group": "com.example",
+        "Name": "SkyPlugin",
+        "Version": "{}",
+        "Description": "A private build",
+        "Authors": [{{ "Name": "Mod Team" }}, "Friend"],
+        "Dependencies": {{ "com.example:CoreLib": "*" }}
+    }}"#, version);
+    archive(env, name, &[("manifest.json", &manifest), ("com/example/SkyPlugin.class", "bytecode")])
+}
+
+fn install(env: &TestEnv, path: &Path, settings: &mut AppSettings) {
+    install_from_file(&env.game_folder, path, ContentType::Mod, settings).unwrap();
+}
+
+#[test]
+fn metadata_is_read_from_the_manifest() {
+    let env = TestEnv::new("local-manifest");
+    let info = read_local_mod(&plugin(&env, "sky-plugin-dev.jar", "1.3.0-dev")).unwrap();
+    assert_eq!(info.mod_id, "com.example.SkyPlugin");
+    assert_eq!(info.name, "SkyPlugin");
+    assert_eq!(info.version, "1.3.0-dev");
+    assert_eq!(info.description.as_deref(), Some("A private build"));
+    assert_eq!(info.authors, vec!["Mod Team", "Friend"]);
+    assert_eq!(info.dependencies, vec!["com.example.CoreLib"]);
+}
+
+#[test]
+fn archives_without_a_manifest_fall_back_to_the_file_name() {
+    let env = TestEnv::new("local-no-manifest");
+    let info = read_local_mod(&archive(&env, "Cozy-Cabins-2.1.0.zip", &[("cabin.prefab", "{}")])).unwrap();
+    assert_eq!(info.mod_id, "cozy-cabins");
+    assert_eq!(info.name, "Cozy Cabins");
+    assert_eq!(info.version, "2.1.0");
+    assert!(info.dependencies.is_empty());
+
+    let not_an_archive = env.root.join("Builds").join("Broken-1.0.0.jar");
+    fs::write(&not_an_archive, b"not a zip").unwrap();
+    assert!(read_local_mod(&not_an_archive).is_err());
+
+    let notes = env.root.join("Builds").join("notes.txt");
+    fs::write(&notes, b"hello").unwrap();
+    assert!(read_local_mod(&notes).is_err());
+}
+
+#[test]
+fn installs_are_recorded_under_the_local_provider() {
+    let env = TestEnv::new("local-install");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let core = archive(&env, "CoreLib-1.0.0.jar", &[("manifest.json", r#"{"Group": "com.example", "Name": "CoreLib"}"#)]);
+    install(&env, &core, &mut settings);
+    install(&env, &plugin(&env, "SkyPlugin-1.3.0.jar", "1.3.0"), &mut settings);
+
+    assert!(env.mods_dir().join("SkyPlugin-1.3.0.jar").is_file());
+    assert!(core.is_file(), "the original file is copied, not moved");
+    let entry = settings.get_installed_mod("SkyPlugin-1.3.0.jar").unwrap();
+    assert_eq!(entry.api_provider, ApiProvider::Local);
+    assert_eq!(entry.mod_id, "com.example.SkyPlugin");
+    assert_eq!(entry.version_name, "1.3.0");
+    assert_eq!(find_dependents(&settings, "CoreLib-1.0.0.jar"), vec!["SkyPlugin-1.3.0.jar"]);
+
+    let reloaded = AppSettings::load_from(settings.get_config_path());
+    assert_eq!(reloaded.get_installed_mod("SkyPlugin-1.3.0.jar").unwrap().api_provider, ApiProvider::Local);
+}
+
+#[test]
+fn a_newer_build_replaces_the_installed_one() {
+    let env = TestEnv::new("local-replace");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    install(&env, &plugin(&env, "SkyPlugin-1.3.0.jar", "1.3.0"), &mut settings);
+    install(&env, &plugin(&env, "SkyPlugin-1.4.0.jar", "1.4.0"), &mut settings);
+
+    assert!(!env.mods_dir().join("SkyPlugin-1.3.0.jar").exists());
+    assert!(env.mods_dir().join("SkyPlugin-1.4.0.jar").is_file());
+    assert_eq!(settings.installed_mods.len(), 1);
+    assert_eq!(settings.get_installed_mod("SkyPlugin-1.4.0.jar").unwrap().version_name, "1.4.0");
+}
+
+#[test]
+fn hostile_manifest_ids_fall_back_to_the_file_name() {
+    let env = TestEnv::new("local-hostile");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let manifest = |version: &str| format!(
+        r#"{{"Name": "../../escape", "Version": "{}", "Dependencies": {{"../../other": "*", "/etc:passwd": "*", "com.example:CoreLib": "*"}}}}"#,
+        version,
+    );
+    install(&env, &archive(&env, "Evil-1.0.0.jar", &[("manifest.json", &manifest("1.0.0"))]), &mut settings);
+    let newer = archive(&env, "Evil-1.1.0.jar", &[("manifest.json", &manifest("1.1.0"))]);
+    let info = read_local_mod(&newer).unwrap();
+    assert_eq!(info.mod_id, "evil");
+    assert_eq!(info.dependencies, vec!["com.example.CoreLib"]);
+
+    // Replacing the build archives the old one under its safe id.
+    install(&env, &newer, &mut settings);
+    assert_eq!(archived_versions(&settings, &ApiProvider::Local, "evil").len(), 1);
+    assert!(!env.root.join("escape").exists());
+    assert!(archived_versions(&settings, &ApiProvider::Local, "../../escape").is_empty());
+}
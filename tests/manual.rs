@@ -0,0 +1,99 @@
+//! Mods whose authors disallow third-party downloads, fetched by hand and
+//! picked up from the downloads folder.
+
+mod common;
+
+use std::fs;
+use std::time::Duration;
+use common::TestEnv;
+use hytale_mod_manager::api::manual::{download_page, find_download, import_download, wait_for_download};
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{get_mod_details_unified, UiMod};
+
+const PAYLOAD: &[u8] = b"SkyIslands-1.0.0.jar test payload\n";
+
+/// Loads settings first, so the lookup goes to the mock server.
+async fn sky_islands(env: &TestEnv) -> UiMod {
+    env.settings(ApiProvider::CurseForge);
+    get_mod_details_unified(&ApiProvider::CurseForge, "1007").await.unwrap()
+}
+
+fn downloads_dir(env: &TestEnv) -> std::path::PathBuf {
+    let dir = env.root.join("Downloads");
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn mods_that_disallow_distribution_need_a_manual_download() {
+    let env = TestEnv::new("manual-flag");
+    let ui_mod = sky_islands(&env).await;
+    assert!(!ui_mod.distribution_allowed);
+    assert!(ui_mod.needs_manual_download(&ui_mod.version));
+    assert_eq!(
+        download_page(&ui_mod, &ui_mod.version),
+        "https://www.curseforge.com/hytale/mods/sky-islands/files/6701"
+    );
+
+    let better_trees = get_mod_details_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    assert!(!better_trees.needs_manual_download(&better_trees.version));
+}
+
+#[tokio::test]
+async fn finds_the_download_by_name_or_checksum() {
+    let env = TestEnv::new("manual-find");
+    let dir = downloads_dir(&env);
+    let version = sky_islands(&env).await.version;
+    assert_eq!(find_download(&dir, &version), None);
+
+    // Partially written, so the size does not match yet.
+    fs::write(dir.join("SkyIslands-1.0.0.jar"), &PAYLOAD[..10]).unwrap();
+    assert_eq!(find_download(&dir, &version), None);
+
+    // The browser renamed the finished file to keep it apart.
+    fs::write(dir.join("SkyIslands-1.0.0 (1).jar"), PAYLOAD).unwrap();
+    assert_eq!(find_download(&dir, &version), Some(dir.join("SkyIslands-1.0.0 (1).jar")));
+
+    fs::write(dir.join("SkyIslands-1.0.0.jar"), PAYLOAD).unwrap();
+    assert_eq!(find_download(&dir, &version), Some(dir.join("SkyIslands-1.0.0.jar")));
+}
+
+#[tokio::test]
+async fn waiting_gives_up_after_the_timeout() {
+    let env = TestEnv::new("manual-wait");
+    let dir = downloads_dir(&env);
+    let version = sky_islands(&env).await.version;
+
+    assert_eq!(wait_for_download(&dir, &version, Some(Duration::ZERO)).await, None);
+
+    let writer_dir = dir.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        fs::write(writer_dir.join("SkyIslands-1.0.0.jar"), PAYLOAD).unwrap();
+    });
+    let found = wait_for_download(&dir, &version, Some(Duration::from_secs(10))).await;
+    assert_eq!(found, Some(dir.join("SkyIslands-1.0.0.jar")));
+}
+
+#[tokio::test]
+async fn imports_the_download_into_the_manifest() {
+    let env = TestEnv::new("manual-import");
+    let ui_mod = sky_islands(&env).await;
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let dir = downloads_dir(&env);
+
+    let corrupt = dir.join("SkyIslands-1.0.0.jar");
+    fs::write(&corrupt, b"SkyIslands-1.0.0.jar wrong payload\n").unwrap();
+    assert!(import_download(&env.game_folder, &corrupt, &ui_mod, &ui_mod.version, &mut settings).is_err());
+    assert!(settings.installed_mods.is_empty());
+
+    let source = dir.join("SkyIslands-1.0.0 (1).jar");
+    fs::write(&source, PAYLOAD).unwrap();
+    import_download(&env.game_folder, &source, &ui_mod, &ui_mod.version, &mut settings).unwrap();
+
+    assert_eq!(fs::read(env.mods_dir().join("SkyIslands-1.0.0.jar")).unwrap(), PAYLOAD);
+    assert!(source.is_file(), "the downloaded copy is left alone");
+    let entry = settings.get_installed_mod("SkyIslands-1.0.0.jar").unwrap();
+    assert_eq!(entry.mod_id, "1007");
+    assert_eq!(entry.file_id, "6701");
+}
@@ -0,0 +1,199 @@
+//! End-to-end provider flows against the recorded fixtures in `tests/fixtures`.
+//! Nothing here touches the network or the real config file.
+
+mod common;
+
+use common::{fixture_bytes, server, TestEnv};
+use hytale_mod_manager::api::curse_forge_api::{ApiResponse, CurseForgeMod};
+use hytale_mod_manager::api::federated::{merge_ranked, search_mods_federated};
+use hytale_mod_manager::api::local_mods::{check_install_status, install_mod, remove_mod, InstallStatus};
+use hytale_mod_manager::api::mod_tale_api::ModTaleMod;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+use hytale_mod_manager::api::ui_mod::{
+    download_version_unified, get_mod_details_unified, get_mod_versions_unified, search_mods_unified, SearchFilter,
+    UiMod,
+};
+
+async fn download_and_install(env: &TestEnv, settings: &mut AppSettings, ui_mod: &UiMod) -> Vec<u8> {
+    let (file_name, bytes) = download_version_unified(&ui_mod.provider, &ui_mod.version).await
+        .expect("download failed");
+    assert_eq!(file_name, ui_mod.version.file_name);
+
+    install_mod(
+        &env.game_folder,
+        &file_name,
+        &bytes,
+        ui_mod.id.clone(),
+        ui_mod.name.clone(),
+        ui_mod.version.file_id.clone(),
+        ui_mod.version.display_name.clone(),
+        ui_mod.provider.clone(),
+        settings.routes.classify_mod(ui_mod),
+        settings,
+    ).expect("install failed");
+
+    bytes
+}
+
+#[tokio::test]
+async fn curseforge_search_install_remove() {
+    let env = TestEnv::new("cf-flow");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+
+    let (mods, total_pages) = search_mods_unified(&settings, 1, "trees".to_string(), 0, &SearchFilter::default()).await.unwrap();
+    assert_eq!(total_pages, 1);
+    assert_eq!(mods.len(), 2);
+    let trees = mods.iter().find(|m| m.id == "1001").unwrap().clone();
+    assert_eq!(trees.name, "Better Trees");
+    assert_eq!(trees.authors, "Oakley");
+    assert_eq!(trees.version.file_id, "5001");
+
+    let search = server().requests_to("/curseforge/mods/search")
+        .into_iter()
+        .find(|r| r.query.get("searchFilter").map(String::as_str) == Some("trees"))
+        .expect("search request not recorded");
+    assert_eq!(search.query.get("gameId").map(String::as_str), Some("70216"));
+    assert_eq!(search.query.get("sortField").map(String::as_str), Some("2"));
+    assert_eq!(search.headers.get("x-api-key").map(String::as_str), Some("test-key"));
+
+    let versions = get_mod_versions_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    let ids: Vec<_> = versions.iter().map(|v| v.file_id.as_str()).collect();
+    assert_eq!(ids, ["5001", "5000"]);
+
+    let bytes = download_and_install(&env, &mut settings, &trees).await;
+    assert_eq!(bytes, fixture_bytes("downloads/BetterTrees-1.2.0.jar"));
+
+    let installed = env.mods_dir().join("BetterTrees-1.2.0.jar");
+    assert_eq!(std::fs::read(&installed).unwrap(), bytes);
+    let entry = settings.get_installed_mod("BetterTrees-1.2.0.jar").unwrap();
+    assert_eq!(entry.mod_id, "1001");
+    assert_eq!(entry.api_provider, ApiProvider::CurseForge);
+    assert_eq!(check_install_status(&settings, "1001", "5001").install_status, InstallStatus::Installed);
+    assert_eq!(check_install_status(&settings, "1001", "5002").install_status, InstallStatus::Outdated);
+
+    // The manifest is persisted next to the test settings, not in the user's config.
+    let reloaded = AppSettings::load_from(env.root.join("settings.json"));
+    assert!(reloaded.get_installed_mod("BetterTrees-1.2.0.jar").is_some());
+
+    remove_mod(&env.game_folder, "BetterTrees-1.2.0.jar", &mut settings).unwrap();
+    assert!(!installed.exists());
+    assert!(settings.installed_mods.is_empty());
+    assert_eq!(check_install_status(&settings, "1001", "5001").install_status, InstallStatus::NotInstalled);
+}
+
+#[tokio::test]
+async fn curseforge_update_replaces_old_file() {
+    let env = TestEnv::new("cf-update");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+
+    let versions = get_mod_versions_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    let mut trees = get_mod_details_unified(&ApiProvider::CurseForge, "1001").await.unwrap();
+    trees.version = versions.iter().find(|v| v.file_id == "5000").unwrap().clone();
+    download_and_install(&env, &mut settings, &trees).await;
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+
+    trees.version = versions.iter().find(|v| v.file_id == "5001").unwrap().clone();
+    download_and_install(&env, &mut settings, &trees).await;
+
+    assert!(!env.mods_dir().join("BetterTrees-1.1.0.jar").exists());
+    assert!(env.mods_dir().join("BetterTrees-1.2.0.jar").is_file());
+    assert_eq!(settings.installed_mods.len(), 1);
+}
+
+#[tokio::test]
+async fn modtale_search_install_remove() {
+    let env = TestEnv::new("mt-flow");
+    let mut settings = env.settings(ApiProvider::Modtale);
+
+    let (mods, _) = search_mods_unified(&settings, 2, "".to_string(), 0, &SearchFilter::default()).await.unwrap();
+    let sky = mods.iter().find(|m| m.id == "mt-91c2").unwrap().clone();
+    assert_eq!(sky.provider, ApiProvider::Modtale);
+    assert_eq!(sky.version.file_name, "skyblock-tools-2.0.0.jar");
+
+    // The server is shared with other tests, so look for this search specifically.
+    let search = server().requests_to("/modtale/projects").into_iter()
+        .find(|r| r.path == "/modtale/projects" && r.query.get("sort").map(String::as_str) == Some("downloads"))
+        .expect("search request not recorded");
+    assert_eq!(search.query.get("size").map(String::as_str), Some("20"));
+
+    let versions = get_mod_versions_unified(&ApiProvider::Modtale, "mt-91c2").await.unwrap();
+    let names: Vec<_> = versions.iter().map(|v| v.display_name.as_str()).collect();
+    assert_eq!(names, ["2.0.0", "1.9.0"]);
+    assert_eq!(sky.version.display_name, "2.0.0");
+
+    // Relative file URLs are resolved against the CDN base.
+    let bytes = download_and_install(&env, &mut settings, &sky).await;
+    assert_eq!(bytes, fixture_bytes("modtale-cdn/files/skyblock-tools-2.0.0.jar"));
+    assert!(!server().requests_to("/modtale-cdn/files/skyblock-tools-2.0.0.jar").is_empty());
+
+    let entry = settings.get_installed_mod("skyblock-tools-2.0.0.jar").unwrap();
+    assert_eq!(entry.api_provider, ApiProvider::Modtale);
+    assert_eq!(entry.version_name, "2.0.0");
+
+    remove_mod(&env.game_folder, "skyblock-tools-2.0.0.jar", &mut settings).unwrap();
+    assert!(!env.mods_dir().join("skyblock-tools-2.0.0.jar").exists());
+    assert!(settings.installed_mods.is_empty());
+}
+
+#[tokio::test]
+async fn details_for_both_providers() {
+    server();
+
+    let cf = get_mod_details_unified(&ApiProvider::CurseForge, "1002").await.unwrap();
+    assert_eq!(cf.name, "Hytale Minimap");
+    assert_eq!(cf.categories, ["Tools"]);
+    assert_eq!(cf.version.release_type, 2);
+
+    let mt = get_mod_details_unified(&ApiProvider::Modtale, "mt-7f3a").await.unwrap();
+    assert_eq!(mt.slug, "better-trees");
+    assert_eq!(mt.website_url, "https://modtale.net/project/better-trees");
+
+    assert!(get_mod_details_unified(&ApiProvider::CurseForge, "9999").await.is_none());
+}
+
+#[tokio::test]
+async fn federated_search_merges_duplicates() {
+    let env = TestEnv::new("federated");
+    let settings = env.settings(ApiProvider::CurseForge);
+
+    let (mods, _) = search_mods_federated(&settings, 1, "".to_string(), 0, &SearchFilter::default()).await.unwrap();
+
+    let names: Vec<_> = mods.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names.iter().filter(|n| **n == "Better Trees").count(), 1);
+    assert!(names.contains(&"Hytale Minimap"));
+    assert!(names.contains(&"Skyblock Tools"));
+
+    let trees = mods.iter().find(|m| m.name == "Better Trees").unwrap();
+    assert_eq!(trees.provider, ApiProvider::CurseForge);
+    assert_eq!(trees.providers(), [ApiProvider::CurseForge, ApiProvider::Modtale]);
+    assert_eq!(trees.other_sources[0].mod_id, "mt-7f3a");
+}
+
+#[test]
+fn a_second_result_from_a_represented_provider_is_kept() {
+    let cf: ApiResponse<CurseForgeMod> = serde_json::from_slice(&fixture_bytes("curseforge/mods/1001.json")).unwrap();
+    let modtale: ModTaleMod = serde_json::from_slice(&fixture_bytes("modtale/projects/mt-7f3a.json")).unwrap();
+    let trees = UiMod::from_modtale_api(&modtale, None);
+    let fork = UiMod { id: "mt-fork".to_string(), ..trees.clone() };
+
+    let merged = merge_ranked(vec![vec![UiMod::from_curseforge_api(&cf.data, None)], vec![trees, fork]]);
+
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].providers(), [ApiProvider::CurseForge, ApiProvider::Modtale]);
+    assert_eq!(merged[0].other_sources[0].mod_id, "mt-7f3a");
+    assert_eq!(merged[1].id, "mt-fork");
+    assert!(merged[1].other_sources.is_empty());
+}
+
+#[test]
+fn fixtures_parse_as_api_models() {
+    let search: ApiResponse<Vec<CurseForgeMod>> = serde_json::from_slice(
+        &fixture_bytes("curseforge/mods/search.json")
+    ).unwrap();
+    assert_eq!(search.data.len(), 2);
+    assert_eq!(search.pagination.unwrap().total_count, 2);
+
+    let modtale: ModTaleMod = serde_json::from_slice(&fixture_bytes("modtale/projects/mt-7f3a.json")).unwrap();
+    assert_eq!(modtale.name, "Better Trees");
+    assert_eq!(modtale.versions.unwrap().len(), 1);
+}
@@ -0,0 +1,122 @@
+//! The shared install queue: parallelism limit, job states, cancel and retry.
+
+mod common;
+
+use std::cell::RefCell;
+use common::TestEnv;
+use hytale_mod_manager::api::checksum::{FileChecksum, HashAlgo};
+use hytale_mod_manager::api::queue::{install_job, InstallQueue, JobStage, JobState};
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{get_mod_details_unified, UiMod};
+
+async fn details(provider: ApiProvider, id: &str) -> UiMod {
+    get_mod_details_unified(&provider, id).await.unwrap()
+}
+
+#[tokio::test]
+async fn limit_holds_later_jobs_in_queue() {
+    let env = TestEnv::new("queue-limit");
+    let settings = RefCell::new(env.settings(ApiProvider::CurseForge));
+    let queue = InstallQueue::new(1);
+
+    let trees = details(ApiProvider::CurseForge, "1001").await;
+    let sky = details(ApiProvider::Modtale, "mt-91c2").await;
+    let first = queue.enqueue(&trees, trees.version.clone());
+    let second = queue.enqueue(&sky, sky.version.clone());
+
+    let run = |id, other| {
+        let (queue, settings, env) = (&queue, &settings, &env);
+        async move {
+            queue.run(id, &env.game_folder, |job, file| {
+                // With one slot, the other job can't have started yet.
+                let other_state = queue.job(other).unwrap().state;
+                assert!(matches!(other_state, JobState::Queued | JobState::Done), "{:?}", other_state);
+                install_job(&env.game_folder, job, file, &mut settings.borrow_mut())
+            }).await
+        }
+    };
+    let (a, b) = tokio::join!(run(first, second), run(second, first));
+
+    assert_eq!(a, JobState::Done);
+    assert_eq!(b, JobState::Done);
+    assert!(env.mods_dir().join("BetterTrees-1.2.0.jar").is_file());
+    assert!(env.mods_dir().join("skyblock-tools-2.0.0.jar").is_file());
+    assert_eq!(settings.borrow().installed_mods.len(), 2);
+
+    queue.clear_finished();
+    assert!(queue.jobs().is_empty());
+}
+
+#[tokio::test]
+async fn cancelled_job_can_be_retried() {
+    let env = TestEnv::new("queue-cancel");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(2);
+
+    let trees = details(ApiProvider::CurseForge, "1001").await;
+    let id = queue.enqueue(&trees, trees.version.clone());
+
+    assert!(queue.cancel(id));
+    let state = queue.run(id, &env.game_folder, |_, _| panic!("cancelled job installed")).await;
+    assert_eq!(state, JobState::Cancelled);
+    assert!(!queue.cancel(id));
+
+    assert!(queue.retry(id));
+    assert_eq!(queue.job(id).unwrap().state, JobState::Queued);
+    let state = queue.run(id, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, &mut settings)).await;
+    assert_eq!(state, JobState::Done);
+    assert!(!queue.retry(id));
+}
+
+#[tokio::test]
+async fn waiting_job_cancels_without_downloading() {
+    let env = TestEnv::new("queue-cancel-waiting");
+    env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(1);
+
+    let trees = details(ApiProvider::CurseForge, "1001").await;
+    let minimap = details(ApiProvider::CurseForge, "1002").await;
+    let first = queue.enqueue(&trees, trees.version.clone());
+    let second = queue.enqueue(&minimap, minimap.version.clone());
+
+    let (a, b) = tokio::join!(
+        queue.run(first, &env.game_folder, |_, _| {
+            assert!(queue.cancel(second));
+            Ok(())
+        }),
+        queue.run(second, &env.game_folder, |_, _| panic!("cancelled job installed")),
+    );
+
+    assert_eq!(a, JobState::Done);
+    assert_eq!(b, JobState::Cancelled);
+    assert_eq!(queue.job(second).unwrap().state, JobState::Cancelled);
+}
+
+#[tokio::test]
+async fn failures_record_the_stage() {
+    let env = TestEnv::new("queue-failure");
+    env.settings(ApiProvider::CurseForge);
+    let queue = InstallQueue::new(1);
+
+    let trees = details(ApiProvider::CurseForge, "1001").await;
+    let mut tampered = trees.version.clone();
+    tampered.checksums = vec![FileChecksum::new(HashAlgo::Md5, &"f".repeat(32)).unwrap()];
+    let id = queue.enqueue(&trees, tampered);
+
+    let state = queue.run(id, &env.game_folder, |_, _| Ok(())).await;
+    assert!(matches!(state, JobState::Failed { stage: JobStage::Verify, .. }), "{:?}", state);
+
+    let mut missing = trees.version.clone();
+    missing.download_url = None;
+    let id = queue.enqueue(&trees, missing);
+    let state = queue.run(id, &env.game_folder, |_, _| Ok(())).await;
+    assert!(matches!(state, JobState::Failed { stage: JobStage::Download, .. }), "{:?}", state);
+
+    let id = queue.enqueue(&trees, trees.version.clone());
+    let state = queue.run(id, &env.game_folder, |_, _| Err("disk full".to_string())).await;
+    assert_eq!(state, JobState::Failed { stage: JobStage::Install, error: "disk full".to_string() });
+
+    assert_eq!(queue.jobs().len(), 3);
+    queue.clear_finished();
+    assert!(queue.jobs().is_empty());
+}
@@ -0,0 +1,88 @@
+//! Picking up interrupted downloads with HTTP range requests.
+
+mod common;
+
+use common::{fixture_bytes, server, TestEnv};
+use hytale_mod_manager::api::download::{get_downloads_dir, partial_path, resume_offset, stream_to_file};
+use hytale_mod_manager::api::provider::registry;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{download_version_to_file_unified, get_mod_versions_unified, UiModVersion};
+
+async fn version(provider: ApiProvider, mod_id: &str, file_id: &str) -> UiModVersion {
+    get_mod_versions_unified(&provider, mod_id).await.unwrap()
+        .into_iter()
+        .find(|v| v.file_id == file_id)
+        .unwrap()
+}
+
+fn ranges(path: &str) -> Vec<Option<String>> {
+    server().requests_to(path).into_iter().map(|r| r.headers.get("range").cloned()).collect()
+}
+
+#[tokio::test]
+async fn dropped_connection_resumes_from_partial() {
+    let env = TestEnv::new("resume-dropped");
+    env.settings(ApiProvider::CurseForge);
+    let latest = version(ApiProvider::CurseForge, "1001", "5001").await;
+
+    let path = "/downloads/BetterTrees-1.2.0.jar";
+    server().truncate_next(path, 10);
+    let download = download_version_to_file_unified(&ApiProvider::CurseForge, &latest, &env.game_folder, None)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&download.path).unwrap(), fixture_bytes("downloads/BetterTrees-1.2.0.jar"));
+    assert_eq!(ranges(path), vec![None, Some("bytes=10-".to_string())]);
+}
+
+#[tokio::test]
+async fn server_ignoring_range_restarts_download() {
+    let env = TestEnv::new("resume-ignored");
+    env.settings(ApiProvider::Modtale);
+    let latest = version(ApiProvider::Modtale, "mt-91c2", "mtv-20").await;
+
+    let path = "/modtale-cdn/files/skyblock-tools-2.0.0.jar";
+    server().ignore_range(path);
+    server().truncate_next(path, 12);
+    let download = download_version_to_file_unified(&ApiProvider::Modtale, &latest, &env.game_folder, None)
+        .await
+        .unwrap();
+
+    assert_eq!(download.size, 45);
+    assert_eq!(std::fs::read(&download.path).unwrap(), fixture_bytes("modtale-cdn/files/skyblock-tools-2.0.0.jar"));
+    assert_eq!(ranges(path), vec![None, Some("bytes=12-".to_string())]);
+}
+
+#[tokio::test]
+async fn partial_survives_until_next_attempt() {
+    let env = TestEnv::new("resume-later");
+    env.settings(ApiProvider::CurseForge);
+    let minimap = version(ApiProvider::CurseForge, "1002", "6001").await;
+    let url = minimap.download_url.clone().unwrap();
+
+    let path = "/downloads/HytaleMinimap-0.4.1.jar";
+    server().truncate_next(path, 20);
+    let api = registry().get(&ApiProvider::CurseForge).unwrap();
+    let resp = api.open_download(&url, 0).await.unwrap();
+    assert!(stream_to_file(resp, &env.game_folder, &minimap, 0, None).await.is_err());
+    assert_eq!(resume_offset(&env.game_folder, &minimap), 20);
+
+    // A different file with the same name can't reuse those bytes.
+    let mut other = minimap.clone();
+    other.file_id = "9999".into();
+    assert_eq!(resume_offset(&env.game_folder, &other), 0);
+    assert!(!partial_path(&env.game_folder, &minimap).exists());
+
+    server().truncate_next(path, 20);
+    let resp = api.open_download(&url, 0).await.unwrap();
+    assert!(stream_to_file(resp, &env.game_folder, &minimap, 0, None).await.is_err());
+
+    let download = download_version_to_file_unified(&ApiProvider::CurseForge, &minimap, &env.game_folder, None)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&download.path).unwrap(), fixture_bytes("downloads/HytaleMinimap-0.4.1.jar"));
+    assert_eq!(ranges(path), vec![None, None, Some("bytes=20-".to_string())]);
+
+    drop(download);
+    assert_eq!(std::fs::read_dir(get_downloads_dir(&env.game_folder)).unwrap().count(), 0);
+}
@@ -0,0 +1,103 @@
+//! Retries, backoff and rate limiting in the shared request layer.
+
+mod common;
+
+use std::time::{Duration, Instant};
+use common::{server, TestEnv};
+use hytale_mod_manager::api::http::{RequestLayer, RetryPolicy, TokenBucket};
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::get_mod_details_unified;
+
+fn fast_layer(max_attempts: u32) -> RequestLayer {
+    RequestLayer::new(
+        "Test",
+        TokenBucket::new(10, 100.0),
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(2),
+        },
+    )
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let policy = RetryPolicy::default();
+    assert_eq!(policy.backoff(0), Duration::from_millis(500));
+    assert_eq!(policy.backoff(1), Duration::from_secs(1));
+    assert_eq!(policy.backoff(2), Duration::from_secs(2));
+    assert_eq!(policy.backoff(12), policy.max_delay);
+}
+
+#[tokio::test]
+async fn server_errors_are_retried_by_providers() {
+    let env = TestEnv::new("retry-5xx");
+    env.settings(ApiProvider::CurseForge);
+
+    server().fail_next("/curseforge/mods/1002", "503 Service Unavailable", None);
+    let ui_mod = get_mod_details_unified(&ApiProvider::CurseForge, "1002").await.unwrap();
+
+    assert_eq!(ui_mod.id, "1002");
+    assert_eq!(server().requests_to("/curseforge/mods/1002").iter().filter(|r| r.path == "/curseforge/mods/1002").count(), 2);
+}
+
+#[tokio::test]
+async fn rate_limit_waits_for_retry_after() {
+    let env = TestEnv::new("retry-429");
+    env.settings(ApiProvider::Modtale);
+
+    server().fail_next("/modtale/projects/mt-7f3a", "429 Too Many Requests", Some(1));
+    let started = Instant::now();
+    let ui_mod = get_mod_details_unified(&ApiProvider::Modtale, "mt-7f3a").await.unwrap();
+
+    assert_eq!(ui_mod.id, "mt-7f3a");
+    assert!(started.elapsed() >= Duration::from_secs(1), "{:?}", started.elapsed());
+    assert_eq!(server().requests_to("/modtale/projects/mt-7f3a").len(), 2);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let path = "/retry/give-up";
+    for _ in 0..3 {
+        server().fail_next(path, "502 Bad Gateway", None);
+    }
+
+    let client = reqwest::Client::new();
+    let resp = fast_layer(3).send(client.get(format!("{}{}", server().base, path))).await.unwrap();
+
+    assert_eq!(resp.status(), reqwest::StatusCode::BAD_GATEWAY);
+    assert_eq!(server().requests_to(path).len(), 3);
+}
+
+#[tokio::test]
+async fn client_errors_and_long_waits_are_not_retried() {
+    let client = reqwest::Client::new();
+    let layer = fast_layer(4);
+
+    let missing = "/retry/missing";
+    let resp = layer.send(client.get(format!("{}{}", server().base, missing))).await.unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+    assert_eq!(server().requests_to(missing).len(), 1);
+
+    let busy = "/retry/busy";
+    server().fail_next(busy, "429 Too Many Requests", Some(120));
+    let resp = layer.send(client.get(format!("{}{}", server().base, busy))).await.unwrap();
+    assert_eq!(resp.status(), reqwest::StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(server().requests_to(busy).len(), 1);
+}
+
+#[tokio::test]
+async fn token_bucket_spaces_out_bursts() {
+    let bucket = TokenBucket::new(2, 20.0);
+    let started = Instant::now();
+    for _ in 0..4 {
+        bucket.acquire().await;
+    }
+    // Two tokens up front, then one every 50ms.
+    assert!(started.elapsed() >= Duration::from_millis(90), "{:?}", started.elapsed());
+
+    bucket.pause(Duration::from_millis(100));
+    let paused = Instant::now();
+    bucket.acquire().await;
+    assert!(paused.elapsed() >= Duration::from_millis(100), "{:?}", paused.elapsed());
+}
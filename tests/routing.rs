@@ -0,0 +1,96 @@
+//! Installing each kind of resource into its own folder of the game.
+
+mod common;
+
+use std::path::PathBuf;
+use common::TestEnv;
+use hytale_mod_manager::api::local_mods::{install_mod, remove_mod, scan_installed, set_route};
+use hytale_mod_manager::api::queue::{install_job, InstallQueue, JobState};
+use hytale_mod_manager::api::routing::{ContentType, RoutingTable};
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+use hytale_mod_manager::api::ui_mod::get_mod_details_unified;
+
+async fn install(env: &TestEnv, settings: &mut AppSettings, id: &str) {
+    let ui_mod = get_mod_details_unified(&ApiProvider::CurseForge, id).await.unwrap();
+    let queue = InstallQueue::new(1);
+    let job = queue.enqueue(&ui_mod, ui_mod.version.clone());
+    let state = queue.run(job, &env.game_folder, |job, file| install_job(&env.game_folder, job, file, settings)).await;
+    assert_eq!(state, JobState::Done);
+}
+
+#[test]
+fn classifies_by_curseforge_class_and_modtale_category() {
+    let routes = RoutingTable::default();
+    assert_eq!(routes.classify(&ApiProvider::CurseForge, Some("9200"), &[]), ContentType::World);
+    assert_eq!(routes.classify(&ApiProvider::CurseForge, Some("9137"), &[]), ContentType::Mod);
+    assert_eq!(routes.classify(&ApiProvider::CurseForge, Some("4242"), &[]), ContentType::Mod);
+    assert_eq!(routes.classify(&ApiProvider::CurseForge, None, &[]), ContentType::Mod);
+
+    // Asset pack and prefab classes have to be configured.
+    let mut configured = routes.clone();
+    configured.curseforge_classes.insert("9300".to_string(), ContentType::AssetPack);
+    configured.curseforge_classes.insert("9400".to_string(), ContentType::Prefab);
+    assert_eq!(configured.classify(&ApiProvider::CurseForge, Some("9300"), &[]), ContentType::AssetPack);
+    assert_eq!(configured.classify(&ApiProvider::CurseForge, Some("9400"), &[]), ContentType::Prefab);
+    assert_eq!(configured.classify(&ApiProvider::CurseForge, Some("9200"), &[]), ContentType::World);
+
+    let categories = vec!["Decoration".to_string(), "prefabs".to_string()];
+    assert_eq!(routes.classify(&ApiProvider::Modtale, None, &categories), ContentType::Prefab);
+    assert_eq!(routes.classify(&ApiProvider::Modtale, None, &["World Generation".to_string()]), ContentType::Mod);
+}
+
+#[tokio::test]
+async fn worlds_install_into_their_own_folder() {
+    let env = TestEnv::new("routing-world");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    install(&env, &mut settings, "1008").await;
+    install(&env, &mut settings, "1001").await;
+
+    let saves = env.game_folder.join("UserData").join("Saves");
+    assert!(saves.join("SkyCastle-1.0.0.zip").is_file());
+    assert!(!env.mods_dir().join("SkyCastle-1.0.0.zip").exists());
+    assert!(env.mods_dir().join("BetterTrees-1.2.0.jar").is_file());
+    assert_eq!(settings.get_installed_mod("SkyCastle-1.0.0.zip").unwrap().content_type, ContentType::World);
+    assert_eq!(scan_installed(&env.game_folder, &settings), vec!["BetterTrees-1.2.0.jar", "SkyCastle-1.0.0.zip"]);
+
+    settings.prune_manifest();
+    assert_eq!(settings.installed_mods.len(), 2);
+
+    remove_mod(&env.game_folder, "SkyCastle-1.0.0.zip", &mut settings).unwrap();
+    assert!(!saves.join("SkyCastle-1.0.0.zip").exists());
+}
+
+#[tokio::test]
+async fn changing_a_route_moves_installed_files() {
+    let env = TestEnv::new("routing-change");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    install(&env, &mut settings, "1008").await;
+    install(&env, &mut settings, "1001").await;
+
+    set_route(&env.game_folder, ContentType::World, PathBuf::from("UserData/Worlds"), &mut settings).unwrap();
+
+    let worlds = env.game_folder.join("UserData").join("Worlds");
+    assert!(worlds.join("SkyCastle-1.0.0.zip").is_file());
+    assert!(!env.game_folder.join("UserData").join("Saves").join("SkyCastle-1.0.0.zip").exists());
+    assert!(env.mods_dir().join("BetterTrees-1.2.0.jar").is_file(), "other types stay put");
+
+    let reloaded = AppSettings::load_from(settings.get_config_path());
+    assert_eq!(reloaded.routes.folder(ContentType::World), PathBuf::from("UserData/Worlds"));
+    settings.prune_manifest();
+    assert!(settings.get_installed_mod("SkyCastle-1.0.0.zip").is_some());
+}
+
+#[test]
+fn install_mod_follows_the_routing_table() {
+    let env = TestEnv::new("routing-install-mod");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    install_mod(
+        &env.game_folder, "SkyCastle-1.0.0.zip", b"world",
+        "1008".into(), "Sky Castle".into(), "7001".into(), "1.0.0".into(),
+        ApiProvider::CurseForge, ContentType::World, &mut settings,
+    ).unwrap();
+
+    assert!(env.game_folder.join("UserData").join("Saves").join("SkyCastle-1.0.0.zip").is_file());
+    assert!(!env.mods_dir().join("SkyCastle-1.0.0.zip").exists());
+    assert_eq!(settings.get_installed_mod("SkyCastle-1.0.0.zip").unwrap().content_type, ContentType::World);
+}
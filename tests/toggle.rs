@@ -0,0 +1,47 @@
+//! Disabling a mod parks it in the holding folder instead of deleting it.
+
+mod common;
+
+use common::TestEnv;
+use hytale_mod_manager::api::local_mods::{get_disabled_dir, remove_mod, set_mod_enabled};
+use hytale_mod_manager::api::settings::ApiProvider;
+
+#[test]
+fn disable_and_enable_move_the_file() {
+    let env = TestEnv::new("toggle-move");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+    let disabled_dir = get_disabled_dir(&env.game_folder);
+
+    set_mod_enabled(&env.game_folder, "BetterTrees-1.1.0.jar", false, &mut settings).unwrap();
+    assert!(!env.mods_dir().join("BetterTrees-1.1.0.jar").exists());
+    assert!(disabled_dir.join("BetterTrees-1.1.0.jar").is_file());
+    assert!(settings.get_installed_mod("BetterTrees-1.1.0.jar").unwrap().disabled);
+
+    // Still installed as far as the manifest is concerned.
+    settings.prune_manifest();
+    assert!(settings.get_installed_mod("BetterTrees-1.1.0.jar").is_some());
+
+    set_mod_enabled(&env.game_folder, "BetterTrees-1.1.0.jar", true, &mut settings).unwrap();
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+    assert!(!settings.get_installed_mod("BetterTrees-1.1.0.jar").unwrap().disabled);
+}
+
+#[test]
+fn disabled_mods_stay_disabled_through_updates_and_can_be_removed() {
+    let env = TestEnv::new("toggle-update");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+    set_mod_enabled(&env.game_folder, "BetterTrees-1.1.0.jar", false, &mut settings).unwrap();
+
+    env.install_mod(&mut settings, "1.2.0").unwrap();
+    let disabled_dir = get_disabled_dir(&env.game_folder);
+    assert!(disabled_dir.join("BetterTrees-1.2.0.jar").is_file());
+    assert!(!disabled_dir.join("BetterTrees-1.1.0.jar").exists());
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+    assert!(settings.get_installed_mod("BetterTrees-1.2.0.jar").unwrap().disabled);
+
+    remove_mod(&env.game_folder, "BetterTrees-1.2.0.jar", &mut settings).unwrap();
+    assert!(!disabled_dir.join("BetterTrees-1.2.0.jar").exists());
+    assert!(settings.installed_mods.is_empty());
+}
@@ -0,0 +1,59 @@
+//! Installs swap files in as one step and put the old version back on failure.
+
+mod common;
+
+use std::fs;
+use common::TestEnv;
+use hytale_mod_manager::api::settings::{ApiProvider, AppSettings};
+
+#[test]
+fn failed_swap_keeps_the_old_version() {
+    let env = TestEnv::new("tx-swap");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+
+    // A directory in the way makes moving the new file into place fail.
+    fs::create_dir_all(env.mods_dir().join("BetterTrees-1.2.0.jar")).unwrap();
+    let err = env.install_mod(&mut settings, "1.2.0").unwrap_err();
+
+    assert!(err.contains("Failed to install"), "{}", err);
+    assert_eq!(fs::read(env.mods_dir().join("BetterTrees-1.1.0.jar")).unwrap(), b"1.1.0 payload");
+    assert_eq!(settings.get_installed_mod("BetterTrees-1.1.0.jar").unwrap().version_name, "1.1.0");
+    assert!(settings.get_installed_mod("BetterTrees-1.2.0.jar").is_none());
+}
+
+#[test]
+fn failed_manifest_save_rolls_back() {
+    let env = TestEnv::new("tx-save");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+
+    // Renaming the saved settings over a directory fails.
+    let blocked = env.root.join("blocked");
+    fs::create_dir_all(&blocked).unwrap();
+    settings.config_path = Some(blocked);
+    let err = env.install_mod(&mut settings, "1.2.0").unwrap_err();
+
+    assert!(err.contains("manifest"), "{}", err);
+    assert!(env.mods_dir().join("BetterTrees-1.1.0.jar").is_file());
+    assert!(!env.mods_dir().join("BetterTrees-1.2.0.jar").exists());
+    assert_eq!(settings.installed_mods.len(), 1);
+    assert!(settings.get_installed_mod("BetterTrees-1.1.0.jar").is_some());
+}
+
+#[test]
+fn update_replaces_the_file_and_leaves_nothing_behind() {
+    let env = TestEnv::new("tx-update");
+    let mut settings = env.settings(ApiProvider::CurseForge);
+    env.install_mod(&mut settings, "1.1.0").unwrap();
+    env.install_mod(&mut settings, "1.2.0").unwrap();
+
+    assert!(!env.mods_dir().join("BetterTrees-1.1.0.jar").exists());
+    assert_eq!(fs::read(env.mods_dir().join("BetterTrees-1.2.0.jar")).unwrap(), b"1.2.0 payload");
+    let leftovers: Vec<_> = fs::read_dir(env.game_folder.join("UserData").join(".downloads")).unwrap().collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+
+    let saved = AppSettings::load_from(env.root.join("settings.json"));
+    assert!(saved.get_installed_mod("BetterTrees-1.2.0.jar").is_some());
+    assert!(!env.root.join("settings.json.tmp").exists());
+}
@@ -0,0 +1,53 @@
+//! Paging through long CurseForge file histories.
+
+mod common;
+
+use common::server;
+use hytale_mod_manager::api::settings::ApiProvider;
+use hytale_mod_manager::api::ui_mod::{get_mod_versions_page_unified, get_mod_versions_unified};
+
+#[tokio::test]
+async fn full_history_follows_every_page() {
+    server();
+
+    let versions = get_mod_versions_unified(&ApiProvider::CurseForge, "1003").await.unwrap();
+    assert_eq!(versions.len(), 120);
+    assert_eq!(versions.first().unwrap().display_name, "Ancient Ruins build 120");
+    assert_eq!(versions.last().unwrap().display_name, "Ancient Ruins build 1");
+
+    let indexes: Vec<_> = server().requests_to("/curseforge/mods/1003/files").iter()
+        .map(|r| r.query.get("index").cloned().unwrap_or_default())
+        .collect();
+    assert!(indexes.contains(&"0".to_string()));
+    assert!(indexes.contains(&"50".to_string()));
+    assert!(indexes.contains(&"100".to_string()));
+}
+
+#[tokio::test]
+async fn pages_report_whether_older_versions_remain() {
+    server();
+
+    let first = get_mod_versions_page_unified(&ApiProvider::CurseForge, "1003", 0).await.unwrap();
+    assert_eq!(first.versions.len(), 50);
+    assert_eq!(first.total_count, Some(120));
+    assert!(first.has_more);
+
+    let last = get_mod_versions_page_unified(&ApiProvider::CurseForge, "1003", 100).await.unwrap();
+    assert_eq!(last.offset, 100);
+    assert_eq!(last.versions.len(), 20);
+    assert!(!last.has_more);
+
+    let short = get_mod_versions_page_unified(&ApiProvider::CurseForge, "1001", 0).await.unwrap();
+    assert_eq!(short.versions.len(), 2);
+    assert!(!short.has_more);
+}
+
+#[tokio::test]
+async fn providers_without_paging_return_one_page() {
+    server();
+
+    let page = get_mod_versions_page_unified(&ApiProvider::Modtale, "mt-91c2", 0).await.unwrap();
+    assert_eq!(page.versions.len(), 2);
+    assert_eq!(page.total_count, Some(2));
+    assert!(!page.has_more);
+}